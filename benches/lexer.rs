@@ -0,0 +1,52 @@
+// Interpreter for Basic C like language
+// Lexer Benchmarks
+//
+// Throughput (MB/s) should stay about the same for every size, as tokenizing takes linear
+// time. Run with 'cargo bench'.
+//
+
+#![feature(test)]
+
+extern crate interpreter;
+extern crate test;
+
+use interpreter::lexer::Lexer;
+use test::Bencher;
+
+// Generated script with the given number of statements, like the scripts written by tools.
+fn generate(statements: usize) -> String {
+    let mut code = String::new();
+
+    for i in 0..statements {
+        code.push_str(&format!("number value{} = {} * (counter + 42) % 7; // Comment {}\n",
+                               i,
+                               i,
+                               i));
+        code.push_str(&format!("string text{} = \"Line ${{value{}}} \\t şğü\";\n", i, i));
+        code.push_str("if (value >= 10) { yaz(text, 'c'); } else { counter = counter - 1; }\n");
+    }
+
+    code
+}
+
+fn bench_tokenize(b: &mut Bencher, statements: usize) {
+    let code = generate(statements);
+    b.bytes = code.len() as u64;
+
+    b.iter(|| Lexer::new(code.clone()).map(|token| token.unwrap()).count());
+}
+
+#[bench]
+fn tokenize_10_kb(b: &mut Bencher) {
+    bench_tokenize(b, 50);
+}
+
+#[bench]
+fn tokenize_100_kb(b: &mut Bencher) {
+    bench_tokenize(b, 500);
+}
+
+#[bench]
+fn tokenize_1_mb(b: &mut Bencher) {
+    bench_tokenize(b, 5000);
+}
@@ -24,37 +24,119 @@ pub enum Expr_ {
     Mul(Box<Expr>, Box<Expr>),
     // Divide two expressions
     Div(Box<Expr>, Box<Expr>),
+    // Remainder of two expressions
+    Mod(Box<Expr>, Box<Expr>),
+    // Negate an expression '-expr'
+    Neg(Box<Expr>),
+    // Check two expressions for equality 'expr == expr'
+    Eq(Box<Expr>, Box<Expr>),
+    // Check two expressions for inequality 'expr != expr'
+    Ne(Box<Expr>, Box<Expr>),
+    // Compare two expressions 'expr < expr'
+    Lt(Box<Expr>, Box<Expr>),
+    // Compare two expressions 'expr > expr'
+    Gt(Box<Expr>, Box<Expr>),
+    // Compare two expressions 'expr <= expr'
+    Le(Box<Expr>, Box<Expr>),
+    // Compare two expressions 'expr >= expr'
+    Ge(Box<Expr>, Box<Expr>),
+    // Use second expression if first one is nil 'expr ?? expr'
+    Coalesce(Box<Expr>, Box<Expr>),
     // Variable expression
     Variable(String),
+    // Array expression '[expr, expr]'
+    Array(Vec<Box<Expr>>),
+    // Index expression 'expr[expr]'
+    Index(Box<Expr>, Box<Expr>),
     // Constant expression
     Constant(Constant),
+    // String with embedded expressions "a ${expr} b", parts are joined like 'print' does
+    Interpolate(Vec<Box<Expr>>),
+    // Declaration expression 'number x = expr' or 'number? x;', first field is declared type,
+    // last one is the text of the doc comments before it '/// text'
+    Declare(Type, String, Option<Box<Expr>>, Option<String>),
     // Assignment expression
     Assign(String, Box<Expr>),
     // If expression 'if expr { expr } else { expr }'
     If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
     // Function Call, first field is name of the function, second is list of arguments
     Call(String, Vec<Box<Expr>>),
+    // Anonymous function 'fn(number x) { expr }', first field is list of parameters
+    Function(Vec<Param>, Box<Expr>),
+    // Return statement, value is optional 'return expr;' or 'return;'
+    Return(Option<Box<Expr>>),
     // Literal expression
     Literal(f64),
     // End of File
     EOF,
-    // Null
+    // Placeholder for a statement that could not be parsed
     Nil,
 }
 
+// Declared type of a variable or a function parameter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Number,
+    String,
+    Char,
+    Bool,
+    Array,
+    Function,
+    // Type of the nil value, it can't be declared
+    Nil,
+    // Type that also accepts nil like 'number?'
+    Optional(Box<Type>),
+}
+
+impl Type {
+    // Check whether a value of the given type can be stored in this type.
+    pub fn accepts(&self, other: &Type) -> bool {
+        match *self {
+            Type::Optional(ref inner) => *other == Type::Nil || inner.accepts(other),
+            ref x => x == other,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Number => write!(f, "number"),
+            Type::String => write!(f, "string"),
+            Type::Char => write!(f, "char"),
+            Type::Bool => write!(f, "bool"),
+            Type::Array => write!(f, "array"),
+            Type::Function => write!(f, "fn"),
+            Type::Nil => write!(f, "nil"),
+            Type::Optional(ref inner) => write!(f, "{}?", inner),
+        }
+    }
+}
+
+// Function parameter like 'number x'
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub param_type: Type,
+    pub name: String,
+}
+
 #[derive(Clone)]
 pub enum Constant {
     String(String),
+    Char(char),
     Number(f64),
     Bool(bool),
+    Nil,
 }
 
 impl fmt::Debug for Constant {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.clone() {
             Constant::String(ref x) => write!(f, "{}", x.to_owned()),
+            Constant::Char(ref x) => write!(f, "{}", x.to_string()),
             Constant::Number(ref x) => write!(f, "{}", x.to_string()),
             Constant::Bool(ref x) => write!(f, "{}", x.to_string()),
+            Constant::Nil => write!(f, "nil"),
         }
     }
 }
@@ -0,0 +1,479 @@
+// Interpreter for Basic C like language
+// Builtins Module
+//
+
+use std::char;
+use std::env;
+use std::f64;
+
+use ast::*;
+use interpreter::Value;
+use language::Language;
+
+// Longest string in bytes that 'repeat' can create.
+const MAX_REPEAT_LENGTH: usize = 1 << 28;
+
+// Call of a built-in function. Messages are in the language of the program and they have
+// the name that the function is called with, like 'uzunluk' for 'length' in Turkish.
+pub struct Call<'a> {
+    pub name: &'a str,
+    pub args: &'a [Value],
+    pub strict: bool, // Math domain violations are errors instead of NaN
+    pub language: Language,
+}
+
+// Built-in function, it is given the call with its parameters.
+type Function = fn(&Call) -> Result<Value, String>;
+
+// Built-in functions by their English names
+static FUNCTIONS: &'static [(&'static str, Function)] = &[
+    ("ord", ord),
+    ("chr", chr),
+    ("toString", to_string),
+    ("toChar", to_char),
+    ("toNumber", to_number),
+    ("toBool", to_bool),
+    ("typeof", type_of),
+    ("env", env_var),
+    ("length", length),
+    ("substring", substring),
+    ("find", find),
+    ("replace", replace),
+    ("split", split),
+    ("join", join),
+    ("trim", trim),
+    ("upper", upper),
+    ("lower", lower),
+    ("startsWith", starts_with),
+    ("endsWith", ends_with),
+    ("repeat", repeat),
+    ("abs", |call| math(call, f64::abs)),
+    ("floor", |call| math(call, f64::floor)),
+    ("ceil", |call| math(call, f64::ceil)),
+    ("round", |call| math(call, f64::round)),
+    ("sqrt", |call| math(call, f64::sqrt)),
+    ("sin", |call| math(call, f64::sin)),
+    ("cos", |call| math(call, f64::cos)),
+    ("tan", |call| math(call, f64::tan)),
+    ("asin", |call| math(call, f64::asin)),
+    ("acos", |call| math(call, f64::acos)),
+    ("atan", |call| math(call, f64::atan)),
+    ("exp", |call| math(call, f64::exp)),
+    ("atan2", |call| math2(call, f64::atan2)),
+    ("pow", |call| math2(call, f64::powf)),
+    ("log", log),
+    ("min", |call| min_max(call, f64::min)),
+    ("max", |call| min_max(call, f64::max)),
+];
+
+// Built-in constants by their English names
+static CONSTANTS: &'static [(&'static str, f64)] = &[
+    ("PI", f64::consts::PI),
+    ("E", f64::consts::E),
+];
+
+// Value of the built-in constant with the given English name.
+pub fn constant(builtin: &str) -> Option<Value> {
+    CONSTANTS.iter().find(|x| x.0 == builtin).map(|x| Value::Number(x.1))
+}
+
+// Call the built-in function with the given English name. In strict mode math functions give
+// an error instead of returning NaN or infinity for parameters outside of their domain.
+// Returns None if there is no built-in function with that name.
+pub fn call(builtin: &str, call: &Call) -> Option<Result<Value, String>> {
+    FUNCTIONS.iter().find(|x| x.0 == builtin).map(|x| (x.1)(call))
+}
+
+impl<'a> Call<'a> {
+    // Error with the message in the language of the program, '{}' in the message are
+    // replaced with the arguments.
+    fn error<T>(&self, message: &str, args: &[&str]) -> Result<T, String> {
+        Err(self.language.message(message, args))
+    }
+
+    // Name of the function for the messages, like '"length"'.
+    fn quoted_name(&self) -> String {
+        format!("{:?}", self.name)
+    }
+
+    fn expect_arity(&self, count: usize) -> Result<(), String> {
+        if self.args.len() == count {
+            Ok(())
+        } else {
+            self.error("{} expects {} parameters but {} given.",
+                       &[&self.quoted_name(), &count.to_string(), &self.args.len().to_string()])
+        }
+    }
+
+    fn type_error<T>(&self, expected: Type, found: &Value) -> Result<T, String> {
+        self.error("{} expects a {} parameter but {} given.",
+                   &[&self.quoted_name(),
+                     &self.language.type_name(&expected),
+                     &self.language.type_name(&found.value_type())])
+    }
+
+    fn string_arg(&self, index: usize) -> Result<&'a str, String> {
+        match self.args[index] {
+            Value::String(ref x) => Ok(x),
+            ref x => self.type_error(Type::String, x),
+        }
+    }
+
+    fn number_arg(&self, index: usize) -> Result<f64, String> {
+        match self.args[index] {
+            Value::Number(x) => Ok(x),
+            ref x => self.type_error(Type::Number, x),
+        }
+    }
+
+    fn array_arg(&self, index: usize) -> Result<&'a [Value], String> {
+        match self.args[index] {
+            Value::Array(ref x) => Ok(x),
+            ref x => self.type_error(Type::Array, x),
+        }
+    }
+
+    // In strict mode a result which is not a finite number for finite parameters is a domain
+    // error.
+    fn checked(&self, params: &[f64], result: f64) -> Result<Value, String> {
+        if self.strict && !result.is_finite() && params.iter().all(|x| x.is_finite()) {
+            let params: Vec<String> = params.iter().map(|x| x.to_string()).collect();
+            self.error("{} is not defined for {}!", &[&self.quoted_name(), &params.join(", ")])
+        } else {
+            Ok(Value::Number(result))
+        }
+    }
+}
+
+// Convert a number to an index of an element in a string or an array of the given length.
+pub fn to_index(value: f64, length: usize, language: Language) -> Result<usize, String> {
+    if value < 0.0 || value.fract() != 0.0 {
+        Err(language.message("{} is not a valid index!", &[&value.to_string()]))
+    } else if value >= length as f64 {
+        Err(language.message("Index {} is out of range for length {}!",
+                             &[&value.to_string(), &length.to_string()]))
+    } else {
+        Ok(value as usize)
+    }
+}
+
+// Convert a number to a position between the elements, it can also be the length itself.
+fn to_bound(value: f64, length: usize, language: Language) -> Result<usize, String> {
+    if value > length as f64 {
+        Err(language.message("Index {} is out of range for length {}!",
+                             &[&value.to_string(), &length.to_string()]))
+    } else {
+        to_index(value, length + 1, language)
+    }
+}
+
+// Code point of a char 'ord('a')'
+fn ord(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    match call.args[0] {
+        Value::Char(x) => Ok(Value::Number(x as u32 as f64)),
+        ref x => call.type_error(Type::Char, x),
+    }
+}
+
+// Char of a code point 'chr(97)'
+fn chr(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    match call.args[0] {
+        Value::Number(x) => {
+            let code = if x >= 0.0 && x.fract() == 0.0 && x <= u32::max_value() as f64 {
+                char::from_u32(x as u32)
+            } else {
+                None
+            };
+
+            match code {
+                Some(c) => Ok(Value::Char(c)),
+                None => call.error("{} is not a valid code point!", &[&x.to_string()]),
+            }
+        }
+        ref x => call.type_error(Type::Number, x),
+    }
+}
+
+// String of any value, formatted like 'print' does 'toString(3.5)'
+fn to_string(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    Ok(Value::String(call.args[0].to_string()))
+}
+
+// Number of a string, char or bool value, nil if the string is not a number 'toNumber("42")'
+fn to_number(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    let string = match call.args[0] {
+        Value::Number(x) => return Ok(Value::Number(x)),
+        Value::Bool(x) => return Ok(Value::Number(if x { 1.0 } else { 0.0 })),
+        Value::String(ref x) => x.clone(),
+        Value::Char(x) => x.to_string(),
+        ref x => return call.type_error(Type::String, x),
+    };
+
+    match string.trim().parse::<f64>() {
+        Ok(x) if x.is_finite() => Ok(Value::Number(x)),
+        _ => Ok(Value::Nil),
+    }
+}
+
+// Bool of a string or number value, nil if the string is not a bool 'toBool("true")'
+fn to_bool(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    match call.args[0] {
+        Value::Bool(x) => Ok(Value::Bool(x)),
+        Value::Number(x) => Ok(Value::Bool(x != 0.0)),
+        Value::String(ref x) => {
+            match x.trim() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Ok(Value::Nil),
+            }
+        }
+        ref x => call.type_error(Type::String, x),
+    }
+}
+
+// Name of the runtime type of a value 'typeof(x)'
+fn type_of(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    Ok(Value::String(call.args[0].value_type().to_string()))
+}
+
+// Value of an environment variable, nil if it is not set 'env("HOME")'
+fn env_var(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+    let name = call.string_arg(0)?;
+
+    // Such names can't be set, reading them may panic on some platforms.
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Ok(Value::Nil);
+    }
+
+    match env::var(name) {
+        Ok(x) => Ok(Value::String(x)),
+        Err(_) => Ok(Value::Nil),
+    }
+}
+
+// Char of a single character string 'toChar("a")'
+fn to_char(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    match call.args[0] {
+        Value::String(ref x) => {
+            let mut chars = x.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Value::Char(c)),
+                _ => {
+                    call.error("{} must have exactly one character to be a char!",
+                               &[&format!("{:?}", x)])
+                }
+            }
+        }
+        ref x => call.type_error(Type::String, x),
+    }
+}
+
+// Character count of a string or element count of an array 'length("abc")'
+fn length(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    match call.args[0] {
+        Value::String(ref x) => Ok(Value::Number(x.chars().count() as f64)),
+        Value::Array(ref x) => Ok(Value::Number(x.len() as f64)),
+        ref x => call.type_error(Type::String, x),
+    }
+}
+
+// Characters between start and end (or end of the string) 'substring("hello", 1, 3)'
+fn substring(call: &Call) -> Result<Value, String> {
+    if call.args.len() != 2 {
+        call.expect_arity(3)?;
+    }
+
+    let string = call.string_arg(0)?;
+    let length = string.chars().count();
+    let start = to_bound(call.number_arg(1)?, length, call.language)?;
+    let end = if call.args.len() == 3 {
+        to_bound(call.number_arg(2)?, length, call.language)?
+    } else {
+        length
+    };
+
+    if start > end {
+        return call.error("Start index {} is greater than end index {}!",
+                          &[&start.to_string(), &end.to_string()]);
+    }
+
+    Ok(Value::String(string.chars().skip(start).take(end - start).collect()))
+}
+
+// Character index of the first occurrence, nil if it is not found 'find("hello", "l")'
+fn find(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let string = call.string_arg(0)?;
+    let pattern = call.string_arg(1)?;
+
+    match string.find(pattern) {
+        Some(x) => Ok(Value::Number(string[..x].chars().count() as f64)),
+        None => Ok(Value::Nil),
+    }
+}
+
+// Replace all occurrences 'replace("hello", "l", "L")'
+fn replace(call: &Call) -> Result<Value, String> {
+    call.expect_arity(3)?;
+
+    let string = call.string_arg(0)?;
+    let from = call.string_arg(1)?;
+    let to = call.string_arg(2)?;
+
+    if from.is_empty() {
+        return call.error("{} can not replace an empty string!", &[&call.quoted_name()]);
+    }
+
+    Ok(Value::String(string.replace(from, to)))
+}
+
+// Array of the parts between separators 'split("a,b", ",")'
+fn split(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let string = call.string_arg(0)?;
+    let separator = call.string_arg(1)?;
+
+    if separator.is_empty() {
+        return call.error("{} separator can not be empty!", &[&call.quoted_name()]);
+    }
+
+    Ok(Value::Array(string.split(separator).map(|x| Value::String(x.to_string())).collect()))
+}
+
+// Join the elements of an array with a separator 'join(parts, ", ")'
+fn join(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let elements = call.array_arg(0)?;
+    let separator = call.string_arg(1)?;
+    let parts: Vec<String> = elements.iter().map(|x| x.to_string()).collect();
+
+    Ok(Value::String(parts.join(separator)))
+}
+
+// Remove whitespace from both ends 'trim("  a  ")'
+fn trim(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    Ok(Value::String(call.string_arg(0)?.trim().to_string()))
+}
+
+// Upper case of a string 'upper("abc")'
+fn upper(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    Ok(Value::String(call.string_arg(0)?.to_uppercase()))
+}
+
+// Lower case of a string 'lower("ABC")'
+fn lower(call: &Call) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    Ok(Value::String(call.string_arg(0)?.to_lowercase()))
+}
+
+// Check the beginning of a string 'startsWith("hello", "he")'
+fn starts_with(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let string = call.string_arg(0)?;
+    Ok(Value::Bool(string.starts_with(call.string_arg(1)?)))
+}
+
+// Check the end of a string 'endsWith("hello", "lo")'
+fn ends_with(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let string = call.string_arg(0)?;
+    Ok(Value::Bool(string.ends_with(call.string_arg(1)?)))
+}
+
+// Repeat a string given times 'repeat("ab", 3)'
+fn repeat(call: &Call) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let string = call.string_arg(0)?;
+    let count = call.number_arg(1)?;
+
+    if count < 0.0 || count.fract() != 0.0 {
+        return call.error("{} count must be a non-negative integer, found {}!",
+                          &[&call.quoted_name(), &count.to_string()]);
+    }
+
+    match string.len().checked_mul(count as usize) {
+        Some(length) if length <= MAX_REPEAT_LENGTH => {
+            Ok(Value::String(string.repeat(count as usize)))
+        }
+        _ => {
+            call.error("{} result is too long, it can be at most {} bytes!",
+                       &[&call.quoted_name(), &MAX_REPEAT_LENGTH.to_string()])
+        }
+    }
+}
+
+// Math function with one number parameter like 'sqrt(2)'
+fn math(call: &Call, operation: fn(f64) -> f64) -> Result<Value, String> {
+    call.expect_arity(1)?;
+
+    let x = call.number_arg(0)?;
+    call.checked(&[x], operation(x))
+}
+
+// Math function with two number parameters like 'pow(2, 10)'
+fn math2(call: &Call, operation: fn(f64, f64) -> f64) -> Result<Value, String> {
+    call.expect_arity(2)?;
+
+    let x = call.number_arg(0)?;
+    let y = call.number_arg(1)?;
+    call.checked(&[x, y], operation(x, y))
+}
+
+// Natural logarithm 'log(x)' or logarithm with a base 'log(x, 2)'
+fn log(call: &Call) -> Result<Value, String> {
+    if call.args.len() != 1 {
+        call.expect_arity(2)?;
+    }
+
+    let x = call.number_arg(0)?;
+    if call.args.len() == 2 {
+        let base = call.number_arg(1)?;
+        call.checked(&[x, base], x.log(base))
+    } else {
+        call.checked(&[x], x.ln())
+    }
+}
+
+// Smallest or greatest of the numbers 'min(3, 1, 2)'
+fn min_max(call: &Call, operation: fn(f64, f64) -> f64) -> Result<Value, String> {
+    if call.args.is_empty() {
+        return call.error("{} expects at least 1 parameter but 0 given.",
+                          &[&call.quoted_name()]);
+    }
+
+    let mut result = call.number_arg(0)?;
+    for i in 1..call.args.len() {
+        result = operation(result, call.number_arg(i)?);
+    }
+
+    Ok(Value::Number(result))
+}
@@ -0,0 +1,446 @@
+// Interpreter for Basic C like language
+// Checker Module
+//
+// Finds type errors without running the program. Types of values are known only for some
+// expressions, so only the errors that would surely happen at runtime are reported.
+//
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ast::*;
+use builtins;
+use language::Language;
+
+#[derive(Debug, Clone)]
+pub struct TypeError {
+    pub message: String,
+    pub language: Language, // Language of the message
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// Declared type of a name, parameters are known if a function literal is assigned to it
+#[derive(Debug, Clone)]
+struct Binding {
+    declared_type: Type,
+    params: Option<Vec<Param>>,
+}
+
+pub struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    function_depth: usize, // Functions can use globals declared after themselves
+    errors: Vec<TypeError>,
+    language: Language, // Language of the built-in names and the messages
+}
+
+impl Default for Checker {
+    fn default() -> Checker {
+        Checker::new()
+    }
+}
+
+impl Checker {
+    pub fn new() -> Checker {
+        Checker::with_language(Language::default())
+    }
+
+    // Checker for a program in the given language, it knows the built-ins by their names in
+    // the language and gives the messages in it.
+    pub fn with_language(language: Language) -> Checker {
+        Checker {
+            scopes: vec![HashMap::new()],
+            function_depth: 0,
+            errors: vec![],
+            language: language,
+        }
+    }
+
+    // Make a global that is defined outside the program known to the checker.
+    pub fn define(&mut self, name: &str, declared_type: Type) {
+        self.bind(name, declared_type, None);
+    }
+
+    pub fn check(mut self, ast: &Expr) -> Result<(), Vec<TypeError>> {
+        self.check_block(ast);
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    // Add an error with the message in the language of the program, '{}' in the message are
+    // replaced with the arguments.
+    fn error(&mut self, message: &str, args: &[&str]) {
+        let message = self.language.message(message, args);
+        self.errors.push(TypeError {
+            message: message,
+            language: self.language,
+        });
+    }
+
+    // Name of the type in the language of the program.
+    fn type_name(&self, value_type: &Type) -> String {
+        self.language.type_name(value_type)
+    }
+
+    fn bind(&mut self, name: &str, declared_type: Type, params: Option<Vec<Param>>) {
+        self.scopes.last_mut().unwrap().insert(name.to_owned(),
+                                               Binding {
+                                                   declared_type: declared_type,
+                                                   params: params,
+                                               });
+    }
+
+    fn lookup(&self, name: &str) -> Option<Binding> {
+        self.scopes.iter().rev().filter_map(|scope| scope.get(name)).next().cloned()
+    }
+
+    fn check_block(&mut self, block: &Expr) {
+        let lines = match block.node {
+            Expr_::Block(ref lines) => lines,
+            _ => return,
+        };
+
+        for line in lines {
+            match line.node {
+                Expr_::Declare(ref var_type, ref identifier, ref value, _) => {
+                    self.check_declare(var_type, identifier, value)
+                }
+                Expr_::Assign(ref identifier, ref value) => self.check_assign(identifier, value),
+                Expr_::Call(ref identifier, ref params) => {
+                    self.check_call(identifier, params);
+                }
+                Expr_::If(ref condition, ref if_block, ref else_block) => {
+                    match self.infer(condition) {
+                        Some(Type::Nil) => {
+                            self.error("If condition is nil, a bool value is needed!", &[])
+                        }
+                        Some(ref x) if is_concrete(x) && *x != Type::Bool => {
+                            self.error("If condition must be a bool value, found {}.",
+                                       &[&self.type_name(x)])
+                        }
+                        _ => {}
+                    }
+
+                    // Blocks of if share the scope they are in.
+                    self.check_block(if_block);
+                    if let Some(ref block) = *else_block {
+                        self.check_block(block);
+                    }
+                }
+                Expr_::Return(Some(ref value)) => {
+                    self.infer(value);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_declare(&mut self, var_type: &Type, identifier: &str, value: &Option<Box<Expr>>) {
+        let value = match *value {
+            Some(ref value) => value,
+            None => return self.bind(identifier, var_type.clone(), None),
+        };
+
+        // Function is known before its body, so it can call itself.
+        let params = function_params(value);
+        if params.is_some() {
+            self.bind(identifier, var_type.clone(), params.clone());
+        }
+
+        if let Some(found) = self.infer(value) {
+            if mismatch(var_type, &found) {
+                self.error("{} is declared as {} but assigned a {} value.",
+                           &[&quote(identifier),
+                             &self.type_name(var_type),
+                             &self.type_name(&found)]);
+            }
+        }
+
+        self.bind(identifier, var_type.clone(), params);
+    }
+
+    fn check_assign(&mut self, identifier: &str, value: &Expr) {
+        let found = self.infer(value);
+
+        let binding = match self.lookup(identifier) {
+            Some(binding) => binding,
+            None => return self.not_found(identifier),
+        };
+
+        if let Some(found) = found {
+            if mismatch(&binding.declared_type, &found) {
+                self.error("{} is a {} variable but assigned a {} value.",
+                           &[&quote(identifier),
+                             &self.type_name(&binding.declared_type),
+                             &self.type_name(&found)]);
+            }
+        }
+
+        // Parameters of the new value are known only if it is a function literal.
+        self.bind(identifier, binding.declared_type, function_params(value));
+    }
+
+    fn check_call(&mut self, identifier: &str, params: &[Box<Expr>]) -> Option<Type> {
+        let binding = self.lookup(identifier);
+
+        // 'read' creates the variables that are not declared as strings.
+        if binding.is_none() && self.language.builtin(identifier) == Some("read") {
+            for param in params {
+                match param.node {
+                    Expr_::Variable(ref var) => {
+                        if self.lookup(var).is_none() {
+                            self.bind(var, Type::String, None);
+                        }
+                    }
+                    _ => self.error("Parameter requires a variable identifier!", &[]),
+                }
+            }
+
+            return None;
+        }
+
+        let args: Vec<Option<Type>> = params.iter().map(|param| self.infer(param)).collect();
+
+        let binding = match binding {
+            Some(binding) => binding,
+            None => return None, // Built-in or native function, or 'print'
+        };
+
+        if !binding.declared_type.accepts(&Type::Function) {
+            self.error("{} is not a function!", &[&quote(identifier)]);
+            return None;
+        }
+
+        if let Some(ref expected) = binding.params {
+            if expected.len() != args.len() {
+                self.error("{} expects {} parameters but {} given.",
+                           &[&quote(identifier),
+                             &expected.len().to_string(),
+                             &args.len().to_string()]);
+                return None;
+            }
+
+            for (param, arg) in expected.iter().zip(args) {
+                match arg {
+                    Some(ref found) if mismatch(&param.param_type, found) => {
+                        self.error("Parameter {} of {} must be {} but {} given.",
+                                   &[&quote(&param.name),
+                                     &quote(identifier),
+                                     &self.type_name(&param.param_type),
+                                     &self.type_name(found)])
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    fn check_function(&mut self, params: &[Param], body: &Expr) {
+        let mut scope = HashMap::new();
+        for param in params {
+            scope.insert(param.name.clone(),
+                         Binding {
+                             declared_type: param.param_type.clone(),
+                             params: None,
+                         });
+        }
+
+        self.scopes.push(scope);
+        self.function_depth += 1;
+        self.check_block(body);
+        self.function_depth -= 1;
+        self.scopes.pop();
+    }
+
+    fn not_found(&mut self, identifier: &str) {
+        // Inside a function the variable may be declared later, before the function is called.
+        if self.function_depth == 0 {
+            self.error("{} variable not found!", &[&quote(identifier)]);
+        }
+    }
+
+    // Type of the expression if it is known without running the program.
+    fn infer(&mut self, expr: &Expr) -> Option<Type> {
+        match expr.node {
+            Expr_::Constant(ref constant) => {
+                Some(match *constant {
+                    Constant::String(_) => Type::String,
+                    Constant::Char(_) => Type::Char,
+                    Constant::Number(_) => Type::Number,
+                    Constant::Bool(_) => Type::Bool,
+                    Constant::Nil => Type::Nil,
+                })
+            }
+            Expr_::Interpolate(ref parts) => {
+                for part in parts {
+                    self.infer(part);
+                }
+
+                Some(Type::String)
+            }
+            Expr_::Variable(ref identifier) => {
+                if let Some(binding) = self.lookup(identifier) {
+                    return Some(binding.declared_type);
+                }
+
+                match self.language.builtin(identifier).and_then(builtins::constant) {
+                    Some(value) => Some(value.value_type()),
+                    None => {
+                        self.not_found(identifier);
+                        None
+                    }
+                }
+            }
+            Expr_::Add(ref lhs, ref rhs) => {
+                match (self.infer(lhs), self.infer(rhs)) {
+                    (Some(Type::Number), Some(Type::Number)) => Some(Type::Number),
+                    (Some(Type::String), Some(Type::String)) => Some(Type::String),
+                    (Some(ref x), Some(ref y)) if is_concrete(x) && is_concrete(y) => {
+                        if *x == Type::Nil || *y == Type::Nil {
+                            self.error("Nil value found where a concrete value is needed!", &[]);
+                        } else {
+                            self.error("Cannot add {} and {} values.",
+                                       &[&self.type_name(x), &self.type_name(y)]);
+                        }
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            Expr_::Sub(ref lhs, ref rhs) |
+            Expr_::Mul(ref lhs, ref rhs) |
+            Expr_::Div(ref lhs, ref rhs) |
+            Expr_::Mod(ref lhs, ref rhs) => {
+                match (self.infer(lhs), self.infer(rhs)) {
+                    (Some(ref x), Some(ref y)) if is_concrete(x) && is_concrete(y) => {
+                        if *x == Type::Nil || *y == Type::Nil {
+                            self.error("Nil value found where a number is needed!", &[]);
+                        } else if *x != Type::Number || *y != Type::Number {
+                            self.error("Arithmetic requires number values, found {} and {}.",
+                                       &[&self.type_name(x), &self.type_name(y)]);
+                        }
+                    }
+                    _ => {}
+                }
+
+                Some(Type::Number)
+            }
+            Expr_::Neg(ref value) => {
+                match self.infer(value) {
+                    Some(Type::Nil) => {
+                        self.error("Nil value found where a number is needed!", &[])
+                    }
+                    Some(ref x) if is_concrete(x) && *x != Type::Number => {
+                        self.error("Cannot negate a {} value.", &[&self.type_name(x)])
+                    }
+                    _ => {}
+                }
+
+                Some(Type::Number)
+            }
+            Expr_::Eq(ref lhs, ref rhs) |
+            Expr_::Ne(ref lhs, ref rhs) => {
+                self.infer(lhs);
+                self.infer(rhs);
+                Some(Type::Bool)
+            }
+            Expr_::Lt(ref lhs, ref rhs) |
+            Expr_::Gt(ref lhs, ref rhs) |
+            Expr_::Le(ref lhs, ref rhs) |
+            Expr_::Ge(ref lhs, ref rhs) => {
+                match (self.infer(lhs), self.infer(rhs)) {
+                    (Some(ref x), Some(ref y)) if is_concrete(x) && is_concrete(y) => {
+                        if *x == Type::Nil || *y == Type::Nil {
+                            self.error("Nil value found where a concrete value is needed!", &[]);
+                        } else if x != y ||
+                                  !(*x == Type::Number || *x == Type::Char ||
+                                    *x == Type::String) {
+                            self.error("Cannot compare {} and {} values.",
+                                       &[&self.type_name(x), &self.type_name(y)]);
+                        }
+                    }
+                    _ => {}
+                }
+
+                Some(Type::Bool)
+            }
+            Expr_::Coalesce(ref lhs, ref rhs) => {
+                let lhs = self.infer(lhs);
+                let rhs = self.infer(rhs);
+
+                match lhs {
+                    Some(Type::Nil) => rhs,
+                    Some(Type::Optional(ref inner)) if rhs.as_ref() == Some(&**inner) => rhs,
+                    Some(ref x) if is_concrete(x) => lhs.clone(),
+                    _ => None,
+                }
+            }
+            Expr_::Array(ref elements) => {
+                for element in elements {
+                    self.infer(element);
+                }
+
+                Some(Type::Array)
+            }
+            Expr_::Index(ref value, ref index) => {
+                match (self.infer(value), self.infer(index)) {
+                    (Some(Type::String), Some(Type::Number)) => Some(Type::Char),
+                    (Some(ref x), Some(ref y)) if is_concrete(x) && is_concrete(y) => {
+                        if *x == Type::Nil || *y == Type::Nil {
+                            self.error("Nil value found where a concrete value is needed!", &[]);
+                        } else if *x != Type::Array || *y != Type::Number {
+                            self.error("Cannot index a {} value with a {} value.",
+                                       &[&self.type_name(x), &self.type_name(y)]);
+                        }
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            Expr_::Call(ref identifier, ref params) => self.check_call(identifier, params),
+            Expr_::Function(ref params, ref body) => {
+                self.check_function(params, body);
+                Some(Type::Function)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Name in the messages like '"count"'
+fn quote(name: &str) -> String {
+    format!("{:?}", name)
+}
+
+// Optional types may hold nil or a value, others always hold a value of their type.
+fn is_concrete(value_type: &Type) -> bool {
+    match *value_type {
+        Type::Optional(_) => false,
+        _ => true,
+    }
+}
+
+// Check whether a value of the found type can never be stored in the expected type.
+fn mismatch(expected: &Type, found: &Type) -> bool {
+    match *found {
+        Type::Optional(ref inner) => !expected.accepts(inner) && !expected.accepts(&Type::Nil),
+        ref x => !expected.accepts(x),
+    }
+}
+
+fn function_params(value: &Expr) -> Option<Vec<Param>> {
+    match value.node {
+        Expr_::Function(ref params, _) => Some(params.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,241 @@
+// Interpreter for Basic C like language
+// Engine Module
+//
+
+use std::cell::Cell;
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+use ast::*;
+use checker::*;
+use lexer::*;
+use parser::*;
+use interpreter::*;
+use language::Language;
+
+/// Error returned from `Engine::eval`, one variant for each stage of the evaluation.
+#[derive(Debug, Clone)]
+pub enum Error {
+    Lex(LexError),
+    Parse(ParseError),
+    Type(Vec<TypeError>),
+    Runtime(RuntimeError),
+}
+
+impl From<LexError> for Error {
+    fn from(error: LexError) -> Error {
+        Error::Lex(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Error {
+        match error.cause {
+            Some(cause) => Error::Lex(cause),
+            None => Error::Parse(error),
+        }
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(error: RuntimeError) -> Error {
+        Error::Runtime(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Lex(ref e) => {
+                write!(f, "{}", e.language.message("Syntax error: {}", &[&e.to_string()]))
+            }
+            Error::Parse(ref e) => {
+                write!(f, "{}", e.language.message("Parse error: {}", &[&e.to_string()]))
+            }
+            Error::Type(ref errors) => {
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", e.language.message("Type error: {}", &[&e.to_string()]))?;
+                }
+                Ok(())
+            }
+            Error::Runtime(ref e) => {
+                write!(f, "{}", e.language.message("Runtime error: {}", &[&e.to_string()]))
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Lex(ref e) => &e.message,
+            Error::Parse(ref e) => &e.message,
+            Error::Type(ref errors) => errors.first().map_or("Type error", |e| &e.message),
+            Error::Runtime(ref e) => &e.message,
+        }
+    }
+}
+
+/// Embeddable interpreter. Globals and functions defined by a script stay in the engine,
+/// so later calls to `eval` can use them.
+///
+/// ```
+/// use interpreter::{Engine, Type, Value};
+///
+/// let mut engine = Engine::new();
+/// engine.set_global("limit", Type::Number, Value::Number(10.0))?;
+///
+/// let result = engine.eval("return limit * 2;")?;
+/// assert_eq!(result, Value::Number(20.0));
+/// # Ok::<(), interpreter::Error>(())
+/// ```
+pub struct Engine {
+    interpreter: Interpreter,
+    lexer_options: LexerOptions,
+    language: Cell<Language>, // Language of the code parsed last, a pragma may select it
+}
+
+impl Engine {
+    /// Engine with the program output on stdout and input from stdin. Messages of the
+    /// interpreter like "Program has ended." are not written, `Interpreter::set_diagnostics`
+    /// gives them a place.
+    pub fn new() -> Engine {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_diagnostics(io::sink());
+
+        Engine {
+            interpreter: interpreter,
+            lexer_options: LexerOptions::default(),
+            language: Cell::new(Language::default()),
+        }
+    }
+
+    /// Options of the lexer for the code given later, like the legacy case-insensitive mode
+    /// or the language of the code.
+    pub fn set_lexer_options(&mut self, options: LexerOptions) {
+        self.lexer_options = options;
+        self.language.set(options.language);
+    }
+
+    /// Tokenize, parse and run the source. Value of a top level `return` is the result,
+    /// otherwise the result is nil.
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        let ast = self.parse(source)?;
+
+        self.run(&ast)
+    }
+
+    /// Run a syntax tree from `parse` or `parse_reader`. Built-in names and the messages are
+    /// in the language of the code parsed last.
+    pub fn run(&mut self, ast: &Expr) -> Result<Value, Error> {
+        self.interpreter.set_language(self.language.get());
+
+        Ok(self.interpreter.run(ast)?)
+    }
+
+    /// Evaluate a single expression like `1 + x` and return its value. A semicolon at the
+    /// end is allowed, statements like declarations are parse errors.
+    pub fn eval_expression(&mut self, source: &str) -> Result<Value, Error> {
+        let ast = self.parse_expression(source)?;
+        self.interpreter.set_language(self.language.get());
+
+        Ok(self.interpreter.evaluate_expression(&ast)?)
+    }
+
+    /// Parse and type-check the source without running it. Globals of the engine are known
+    /// to the checker.
+    pub fn check(&self, source: &str) -> Result<(), Error> {
+        let ast = self.parse(source)?;
+
+        self.check_ast(&ast)
+    }
+
+    /// Type-check a syntax tree from `parse` or `parse_reader`, it is in the language of the
+    /// code parsed last.
+    pub fn check_ast(&self, ast: &Expr) -> Result<(), Error> {
+        let mut checker = Checker::with_language(self.language.get());
+        for (name, declared_type, _) in self.interpreter.globals() {
+            checker.define(&name, declared_type);
+        }
+
+        checker.check(ast).map_err(Error::Type)
+    }
+
+    /// Tokens of the source, including comments and the final EOF token.
+    pub fn tokenize(&self, source: &str) -> Result<Vec<Token>, Error> {
+        Ok(self.lexer(source.to_string()).collect::<Result<Vec<Token>, LexError>>()?)
+    }
+
+    /// Syntax tree of the source.
+    pub fn parse(&self, source: &str) -> Result<Box<Expr>, Error> {
+        self.parse_tokens(TokenStream::from_lexer(self.lexer(source.to_string())))
+    }
+
+    /// Syntax tree of the code read from the reader. Code is tokenized while it is read, so
+    /// the tokens of the whole code are never kept in memory.
+    pub fn parse_reader<R: BufRead + 'static>(&self, reader: R) -> Result<Box<Expr>, Error> {
+        self.parse_tokens(TokenStream::from_lexer(Lexer::with_options(reader, self.lexer_options)))
+    }
+
+    fn lexer(&self, code: String) -> Lexer {
+        Lexer::with_options(io::Cursor::new(code.into_bytes()), self.lexer_options)
+    }
+
+    fn parse_tokens(&self, token_stream: TokenStream) -> Result<Box<Expr>, Error> {
+        let mut parser = Parser::new(token_stream, None);
+        let result = parser.parse();
+        self.language.set(parser.token_stream.options().language);
+
+        Ok(result?)
+    }
+
+    /// Syntax tree of a single expression, a semicolon at the end is allowed.
+    pub fn parse_expression(&self, source: &str) -> Result<Box<Expr>, Error> {
+        let code = source.trim_end();
+        let code = if code.ends_with(';') { &code[..code.len() - 1] } else { code };
+
+        // Line break keeps the right parenthesis out of a comment at the end.
+        let token_stream = TokenStream::from_lexer(self.lexer(format!("({}\n)", code)));
+        let mut parser = Parser::new(token_stream, None);
+        let result = parser.parse_wrapped_expression();
+        self.language.set(parser.token_stream.options().language);
+
+        Ok(result?)
+    }
+
+    /// Define a global variable of the declared type, an existing one is replaced. Value must
+    /// be of that type, nil needs an optional type like `Type::Optional(Box::new(Type::Number))`.
+    pub fn set_global(&mut self,
+                      name: &str,
+                      declared_type: Type,
+                      value: Value)
+                      -> Result<(), Error> {
+        Ok(self.interpreter.set_global(name, declared_type, value)?)
+    }
+
+    /// Current value of a global variable.
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.interpreter.get_global(name)
+    }
+
+    /// Status given to `exit(code)` if the last evaluation is ended with it.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.interpreter.exit_code()
+    }
+
+    /// Underlying interpreter, for settings like strict mode, call depth, native functions
+    /// and I/O.
+    pub fn interpreter(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::new()
+    }
+}
@@ -2,167 +2,980 @@
 // Interpreter Module
 //
 
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::io::{self, BufRead};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::mem;
+use std::rc::Rc;
 
 use ast::*;
-use parser::*;
+use builtins::{self, Call};
+use language::Language;
 
-// Symbol Value Enum for Symbol Table
-#[allow(dead_code)]
+// Symbol Struct for Symbol Table
 #[derive(Debug, Clone)]
-enum SymbolType {
-    Variable,
-    Function,
+struct Symbol {
+    declared_type: Type,
+    value: Value,
 }
 
-// Symbol Struct for Symbol Table
+impl Symbol {
+    fn new(declared_type: Type, value: Value) -> Symbol {
+        Symbol {
+            declared_type: declared_type,
+            value: value,
+        }
+    }
+}
+
+// Symbol Table of a scope with a link to its enclosing scope
+#[derive(Debug)]
+struct Environment {
+    symbols: HashMap<String, Symbol>,
+    parent: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    fn new(parent: Option<Rc<RefCell<Environment>>>) -> Rc<RefCell<Environment>> {
+        Rc::new(RefCell::new(Environment {
+            symbols: HashMap::new(),
+            parent: parent,
+        }))
+    }
+
+    fn get(&self, identifier: &str) -> Option<Symbol> {
+        match self.symbols.get(identifier) {
+            Some(symbol) => Some(symbol.clone()),
+            None => {
+                match self.parent {
+                    Some(ref parent) => parent.borrow().get(identifier),
+                    None => None,
+                }
+            }
+        }
+    }
+
+    fn define(&mut self, identifier: &str, declared_type: Type, value: Value) {
+        self.symbols.insert(identifier.to_owned(), Symbol::new(declared_type, value));
+    }
+
+    // Assign to the nearest scope that has the variable, returns false if there is none.
+    fn assign(&mut self, identifier: &str, value: Value) -> bool {
+        if let Some(symbol) = self.symbols.get_mut(identifier) {
+            symbol.value = value;
+            return true;
+        }
+
+        match self.parent {
+            Some(ref parent) => parent.borrow_mut().assign(identifier, value),
+            None => false,
+        }
+    }
+}
+
+// Function value with the scope it is created in
+pub struct Function {
+    params: Vec<Param>,
+    body: Box<Expr>,
+    closure: Rc<RefCell<Environment>>,
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fn({} params)>", self.params.len())
+    }
+}
+
+// Runtime value of an expression
 #[derive(Debug, Clone)]
-struct Symbol {
-    symbol_type: SymbolType,
-    value: Constant,
+pub enum Value {
+    String(String),
+    Char(char),
+    Number(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Function(Rc<Function>),
+    Nil,
+}
+
+impl Value {
+    pub fn value_type(&self) -> Type {
+        match *self {
+            Value::String(_) => Type::String,
+            Value::Char(_) => Type::Char,
+            Value::Number(_) => Type::Number,
+            Value::Bool(_) => Type::Bool,
+            Value::Array(_) => Type::Array,
+            Value::Function(_) => Type::Function,
+            Value::Nil => Type::Nil,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (&Value::String(ref x), &Value::String(ref y)) => x == y,
+            (&Value::Char(x), &Value::Char(y)) => x == y,
+            (&Value::Number(x), &Value::Number(y)) => x == y,
+            (&Value::Bool(x), &Value::Bool(y)) => x == y,
+            (&Value::Array(ref x), &Value::Array(ref y)) => x == y,
+            (&Value::Function(ref x), &Value::Function(ref y)) => Rc::ptr_eq(x, y),
+            (&Value::Nil, &Value::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> From<&'a Constant> for Value {
+    fn from(constant: &'a Constant) -> Value {
+        match *constant {
+            Constant::String(ref x) => Value::String(x.clone()),
+            Constant::Char(x) => Value::Char(x),
+            Constant::Number(x) => Value::Number(x),
+            Constant::Bool(x) => Value::Bool(x),
+            Constant::Nil => Value::Nil,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::String(ref x) => write!(f, "{}", x),
+            Value::Char(ref x) => write!(f, "{}", x),
+            Value::Number(ref x) => write!(f, "{}", x),
+            Value::Bool(ref x) => write!(f, "{}", x),
+            Value::Array(ref x) => {
+                write!(f, "[")?;
+                for (i, element) in x.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Value::Function(_) => write!(f, "<fn>"),
+            Value::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+// Reasons to leave a block before its end
+enum Unwind {
+    Return(Value),
+    Exit(i32), // Program is ended with 'exit(code)'
+    Error(String),
+}
+
+type Exec<T> = Result<T, Unwind>;
+
+fn runtime_error<T>(message: String) -> Exec<T> {
+    Err(Unwind::Error(message))
+}
+
+// Name in the messages like '"count"'
+fn quote(name: &str) -> String {
+    format!("{:?}", name)
+}
+
+// Error that stops the program while it is running
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub message: String,
+    pub language: Language, // Language of the message
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+// Nested function calls deeper than this are an error instead of a stack overflow. Each call
+// needs a few kilobytes of stack and a debug build needs more, the default is safe on a thread
+// with 2 MB of stack.
+const DEFAULT_MAX_CALL_DEPTH: usize = 64;
+
+// Rust function registered by the host application
+struct NativeFunction {
+    params: Vec<Type>,
+    function: Rc<dyn Fn(&[Value]) -> Result<Value, String>>,
 }
 
 pub struct Interpreter {
-    ast: Box<Expr>,
-    symbol_table: HashMap<String, Symbol>,
+    globals: Rc<RefCell<Environment>>, // Top level scope, kept between runs
+    env: Rc<RefCell<Environment>>, // Current scope
+    natives: HashMap<String, NativeFunction>,
+    strict: bool, // Math domain violations are errors instead of NaN
+    output: Box<dyn Write>, // Program output written by 'print'
+    input: Box<dyn BufRead>, // Program input read by 'read'
+    diagnostics: Box<dyn Write>, // Messages of the interpreter itself
+    input_line: String, // Unread rest of the current input line
+    call_depth: usize, // Number of function calls that are running
+    max_call_depth: usize,
+    args: Vec<String>, // Script arguments returned by 'args()'
+    exit_code: Option<i32>, // Status given to 'exit(code)' in the last run
+    language: Language, // Language of the built-in names and the messages
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
 }
 
 impl Interpreter {
-    pub fn new(mut _parser: Parser) -> Interpreter {
-        // let temp = &_parser;
+    pub fn new() -> Interpreter {
+        let globals = Environment::new(None);
+
         Interpreter {
-            ast: _parser.parse(),
-            symbol_table: HashMap::new(),
+            globals: globals.clone(),
+            env: globals,
+            natives: HashMap::new(),
+            strict: false,
+            output: Box::new(io::stdout()),
+            input: Box::new(BufReader::new(io::stdin())),
+            diagnostics: Box::new(io::stderr()),
+            input_line: "".to_string(),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            args: vec![],
+            exit_code: None,
+            language: Language::default(),
+        }
+    }
+
+    // Program output goes to stdout by default.
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.output = Box::new(output);
+    }
+
+    // Program input comes from stdin by default.
+    pub fn set_input<R: BufRead + 'static>(&mut self, input: R) {
+        self.input = Box::new(input);
+        self.input_line = "".to_string();
+    }
+
+    // Interpreter messages like "Program has ended." go to stderr by default.
+    pub fn set_diagnostics<W: Write + 'static>(&mut self, diagnostics: W) {
+        self.diagnostics = Box::new(diagnostics);
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // Calls nested deeper than the limit stop the program with a runtime error instead of
+    // overflowing the stack. The thread that runs the program needs enough stack for them,
+    // the default limit is safe on a thread with 2 MB of stack.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    // Arguments given to the script, scripts read them with 'args()'.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    // Programs call the built-ins by their names in the language and the messages are in it,
+    // English is the default.
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    // Register a Rust function that scripts can call by name. Arguments are checked against
+    // the parameter types before the function is called, an error returned from the function
+    // stops the program as a runtime error. It takes precedence over built-in functions like
+    // 'print', but functions declared by the script take precedence over it.
+    pub fn register_function<F>(&mut self, name: &str, params: Vec<Type>, function: F)
+        where F: Fn(&[Value]) -> Result<Value, String> + 'static
+    {
+        self.natives.insert(name.to_owned(),
+                            NativeFunction {
+                                params: params,
+                                function: Rc::new(function),
+                            });
+    }
+
+    // Define a global variable of the declared type, an existing one is replaced. Value must
+    // be of that type, nil needs an optional type like 'number?'.
+    pub fn set_global(&mut self,
+                      name: &str,
+                      declared_type: Type,
+                      value: Value)
+                      -> Result<(), RuntimeError> {
+        if declared_type == Type::Nil {
+            let message = "{} can't be declared as nil, an optional type is needed.";
+            return Err(RuntimeError {
+                message: self.language.message(message, &[&quote(name)]),
+                language: self.language,
+            });
+        }
+
+        if !declared_type.accepts(&value.value_type()) {
+            let message = "{} is declared as {} but assigned a {} value.";
+            return Err(RuntimeError {
+                message: self.language.message(message,
+                                               &[&quote(name),
+                                                 &self.language.type_name(&declared_type),
+                                                 &self.language
+                                                     .type_name(&value.value_type())]),
+                language: self.language,
+            });
         }
+
+        self.globals.borrow_mut().define(name, declared_type, value);
+        Ok(())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.borrow().symbols.get(name).map(|symbol| symbol.value.clone())
     }
 
-    pub fn run(&mut self) {
-        let node = self.ast.node.clone();
-        self.run_block(node);
+    // Global variables with their declared types, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Type, Value)> {
+        let mut globals: Vec<(String, Type, Value)> = self.globals
+            .borrow()
+            .symbols
+            .iter()
+            .map(|(name, symbol)| {
+                (name.clone(), symbol.declared_type.clone(), symbol.value.clone())
+            })
+            .collect();
+
+        globals.sort_by(|x, y| x.0.cmp(&y.0));
+        globals
+    }
+
+    // Run a parsed program in the global scope. Value of a top level return is the result,
+    // otherwise the result is nil. Program ended with 'exit(code)' also gives nil, and the
+    // status is kept for 'exit_code'.
+    pub fn run(&mut self, ast: &Expr) -> Result<Value, RuntimeError> {
+        self.env = self.globals.clone();
+        self.exit_code = None;
+
+        let result = self.run_block(ast).map(|_| Value::Nil);
+        self.finish(result)
     }
 
-    fn run_block(&mut self, expr: Expr_) {
-        match expr {
+    // Evaluate an expression in the global scope, like the expressions typed in REPL.
+    pub fn evaluate_expression(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.env = self.globals.clone();
+        self.exit_code = None;
+
+        let result = self.evaluate(expr);
+        self.finish(result)
+    }
+
+    // Status given to 'exit(code)' if the last run is ended with it.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    fn finish(&mut self, result: Exec<Value>) -> Result<Value, RuntimeError> {
+        let _ = self.output.flush();
+
+        match result {
+            Ok(value) |
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Exit(code)) => {
+                self.exit_code = Some(code);
+                Ok(Value::Nil)
+            }
+            Err(Unwind::Error(message)) => {
+                Err(RuntimeError {
+                    message: message,
+                    language: self.language,
+                })
+            }
+        }
+    }
+
+    // Runtime error with the message in the language of the program, '{}' in the message are
+    // replaced with the arguments.
+    fn error<T>(&self, message: &str, args: &[&str]) -> Exec<T> {
+        runtime_error(self.language.message(message, args))
+    }
+
+    // Name of the type of the value in the language of the program.
+    fn type_name(&self, value: &Value) -> String {
+        self.language.type_name(&value.value_type())
+    }
+
+    fn diagnostic(&mut self, message: &str) {
+        let message = self.language.message(message, &[]);
+        let _ = writeln!(self.diagnostics, "{}", message);
+    }
+
+    fn run_block(&mut self, block: &Expr) -> Exec<()> {
+        match block.node {
             Expr_::Block(ref lines) => {
                 for line in lines {
                     match line.node {
+                        Expr_::Declare(ref var_type, ref identifier, ref value, _) => {
+                            self.interpret_declare(var_type, identifier, value)?
+                        }
                         Expr_::Assign(ref identifier, ref value) => {
-                            self.interpret_assign(identifier, value)
+                            self.interpret_assign(identifier, value)?
                         }
                         Expr_::Call(ref identifier, ref params) => {
-                            self.interpret_call(identifier, params)
+                            self.interpret_call(identifier, params)?;
+                        }
+                        Expr_::If(ref condition, ref if_block, ref else_block) => {
+                            self.interpret_if(condition, if_block, else_block)?
+                        }
+                        Expr_::Return(ref value) => {
+                            let value = match *value {
+                                Some(ref expr) => self.evaluate(expr)?,
+                                None => Value::Nil,
+                            };
+                            return Err(Unwind::Return(value));
+                        }
+                        Expr_::EOF => self.diagnostic("Program has ended."),
+                        _ => self.diagnostic("Unimplemented feature found!"),
+                    }
+                }
+            }
+
+            _ => self.diagnostic("Block not found!"),
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Exec<Value> {
+        match expr.node {
+            Expr_::Constant(ref constant) => Ok(Value::from(constant)),
+            Expr_::Interpolate(ref parts) => Ok(Value::String(self.concat(parts)?)),
+            Expr_::Variable(ref identifier) => {
+                if let Some(symbol) = self.env.borrow().get(identifier) {
+                    return Ok(symbol.value);
+                }
+
+                match self.language.builtin(identifier).and_then(builtins::constant) {
+                    Some(value) => Ok(value),
+                    None => self.error("{} variable not found!", &[&quote(identifier)]),
+                }
+            }
+            Expr_::Add(ref lhs, ref rhs) => {
+                match (self.evaluate(lhs)?, self.evaluate(rhs)?) {
+                    (Value::Number(x), Value::Number(y)) => Ok(Value::Number(x + y)),
+                    (Value::String(x), Value::String(y)) => Ok(Value::String(x + &y)),
+                    (Value::Nil, _) | (_, Value::Nil) => {
+                        self.error("Nil value found where a concrete value is needed!", &[])
+                    }
+                    (x, y) => {
+                        self.error("Cannot add {} and {} values.",
+                                   &[&self.type_name(&x), &self.type_name(&y)])
+                    }
+                }
+            }
+            Expr_::Sub(ref lhs, ref rhs) => self.arithmetic(lhs, rhs, |x, y| x - y),
+            Expr_::Mul(ref lhs, ref rhs) => self.arithmetic(lhs, rhs, |x, y| x * y),
+            Expr_::Div(ref lhs, ref rhs) => self.arithmetic(lhs, rhs, |x, y| x / y),
+            Expr_::Mod(ref lhs, ref rhs) => self.arithmetic(lhs, rhs, |x, y| x % y),
+            Expr_::Neg(ref value) => {
+                match self.evaluate(value)? {
+                    Value::Number(x) => Ok(Value::Number(-x)),
+                    Value::Nil => self.error("Nil value found where a number is needed!", &[]),
+                    x => self.error("Cannot negate a {} value.", &[&self.type_name(&x)]),
+                }
+            }
+            Expr_::Eq(ref lhs, ref rhs) => {
+                Ok(Value::Bool(self.evaluate(lhs)? == self.evaluate(rhs)?))
+            }
+            Expr_::Ne(ref lhs, ref rhs) => {
+                Ok(Value::Bool(self.evaluate(lhs)? != self.evaluate(rhs)?))
+            }
+            Expr_::Lt(ref lhs, ref rhs) => self.compare(lhs, rhs, |x| x == Ordering::Less),
+            Expr_::Gt(ref lhs, ref rhs) => self.compare(lhs, rhs, |x| x == Ordering::Greater),
+            Expr_::Le(ref lhs, ref rhs) => self.compare(lhs, rhs, |x| x != Ordering::Greater),
+            Expr_::Ge(ref lhs, ref rhs) => self.compare(lhs, rhs, |x| x != Ordering::Less),
+            Expr_::Coalesce(ref lhs, ref rhs) => {
+                // Second expression is evaluated only if it is needed.
+                match self.evaluate(lhs)? {
+                    Value::Nil => self.evaluate(rhs),
+                    value => Ok(value),
+                }
+            }
+            Expr_::Array(ref elements) => {
+                let mut values: Vec<Value> = vec![];
+                for element in elements {
+                    values.push(self.evaluate(element)?);
+                }
+
+                Ok(Value::Array(values))
+            }
+            Expr_::Index(ref value, ref index) => {
+                match (self.evaluate(value)?, self.evaluate(index)?) {
+                    (Value::String(x), Value::Number(i)) => {
+                        match builtins::to_index(i, x.chars().count(), self.language) {
+                            Ok(i) => Ok(Value::Char(x.chars().nth(i).unwrap())),
+                            Err(message) => runtime_error(message),
                         }
-                        Expr_::If(ref identifier, ref if_block, ref else_block) => {
-                            self.interpret_if(identifier, if_block, else_block)
+                    }
+                    (Value::Array(mut x), Value::Number(i)) => {
+                        match builtins::to_index(i, x.len(), self.language) {
+                            Ok(i) => Ok(x.swap_remove(i)),
+                            Err(message) => runtime_error(message),
                         }
-                        Expr_::EOF => println!("Program has ended."),
-                        _ => println!("Unimplemented feature found!"),
+                    }
+                    (Value::Nil, _) | (_, Value::Nil) => {
+                        self.error("Nil value found where a concrete value is needed!", &[])
+                    }
+                    (x, y) => {
+                        self.error("Cannot index a {} value with a {} value.",
+                                   &[&self.type_name(&x), &self.type_name(&y)])
                     }
                 }
             }
+            Expr_::Call(ref identifier, ref params) => self.interpret_call(identifier, params),
+            Expr_::Function(ref params, ref body) => {
+                Ok(Value::Function(Rc::new(Function {
+                    params: params.clone(),
+                    body: body.clone(),
+                    closure: self.env.clone(),
+                })))
+            }
+            _ => self.error("Unexpected expression found!", &[]),
+        }
+    }
 
-            _ => println!("Block not found!"),
+    fn arithmetic<F>(&mut self, lhs: &Expr, rhs: &Expr, operation: F) -> Exec<Value>
+        where F: Fn(f64, f64) -> f64
+    {
+        match (self.evaluate(lhs)?, self.evaluate(rhs)?) {
+            (Value::Number(x), Value::Number(y)) => Ok(Value::Number(operation(x, y))),
+            (Value::Nil, _) | (_, Value::Nil) => {
+                self.error("Nil value found where a number is needed!", &[])
+            }
+            (x, y) => {
+                self.error("Arithmetic requires number values, found {} and {}.",
+                           &[&self.type_name(&x), &self.type_name(&y)])
+            }
         }
     }
 
-    fn interpret_assign(&mut self, identifier: &str, value: &Box<Expr>) {
-        match value.node {
-            Expr_::Constant(ref constant) => {
-                self.symbol_table.insert(identifier.to_owned(),
-                                         Symbol {
-                                             symbol_type: SymbolType::Variable,
-                                             value: constant.to_owned(),
-                                         });
+    fn compare<F>(&mut self, lhs: &Expr, rhs: &Expr, operation: F) -> Exec<Value>
+        where F: Fn(Ordering) -> bool
+    {
+        let ordering = match (self.evaluate(lhs)?, self.evaluate(rhs)?) {
+            (Value::Number(x), Value::Number(y)) => x.partial_cmp(&y),
+            (Value::Char(x), Value::Char(y)) => Some(x.cmp(&y)),
+            (Value::String(x), Value::String(y)) => Some(x.cmp(&y)),
+            (Value::Nil, _) | (_, Value::Nil) => {
+                return self.error("Nil value found where a concrete value is needed!", &[])
+            }
+            (x, y) => {
+                return self.error("Cannot compare {} and {} values.",
+                                  &[&self.type_name(&x), &self.type_name(&y)])
+            }
+        };
+
+        // NaN is not ordered, so every comparison with it is false.
+        Ok(Value::Bool(ordering.map_or(false, operation)))
+    }
+
+    fn interpret_declare(&mut self,
+                         var_type: &Type,
+                         identifier: &str,
+                         value: &Option<Box<Expr>>)
+                         -> Exec<()> {
+        // Variables declared without a value are nil until they are assigned.
+        let value = match *value {
+            Some(ref expr) => {
+                let value = self.evaluate(expr)?;
+
+                if !var_type.accepts(&value.value_type()) {
+                    return self.error("{} is declared as {} but assigned a {} value.",
+                                      &[&quote(identifier),
+                                        &self.language.type_name(var_type),
+                                        &self.type_name(&value)]);
+                }
+
+                value
             }
-            _ => unimplemented!(),
+            None => Value::Nil,
         };
+
+        self.env.borrow_mut().define(identifier, var_type.clone(), value);
+        Ok(())
     }
 
-    fn interpret_call(&mut self, identifier: &str, params: &[Box<Expr>]) {
-        if &*identifier == "yaz" {
-            self.print(params.to_owned());
-        } else if &*identifier == "oku" {
-            self.get(params.to_owned());
+    fn interpret_assign(&mut self, identifier: &str, value: &Expr) -> Exec<()> {
+        let value = self.evaluate(value)?;
+
+        let declared_type = match self.env.borrow().get(identifier) {
+            Some(symbol) => symbol.declared_type,
+            None => return self.error("{} variable not found!", &[&quote(identifier)]),
+        };
+
+        if !declared_type.accepts(&value.value_type()) {
+            return self.error("{} is a {} variable but assigned a {} value.",
+                              &[&quote(identifier),
+                                &self.language.type_name(&declared_type),
+                                &self.type_name(&value)]);
         }
+
+        self.env.borrow_mut().assign(identifier, value);
+        Ok(())
     }
 
-    fn interpret_if(&mut self,
-                    identifier: &Box<Expr>,
-                    if_block: &Box<Expr>,
-                    else_block: &Option<Box<Expr>>) {
-        let mut variable: Symbol = Symbol {
-            symbol_type: SymbolType::Variable,
-            value: Constant::String("Uninitilized variable found!".to_string()),
+    fn interpret_call(&mut self, identifier: &str, params: &[Box<Expr>]) -> Exec<Value> {
+        let symbol = self.env.borrow().get(identifier);
+
+        match symbol {
+            Some(Symbol { value: Value::Function(function), .. }) => {
+                let mut args: Vec<Value> = vec![];
+                for param in params {
+                    args.push(self.evaluate(param)?);
+                }
+
+                self.call_function(identifier, &function, args)
+            }
+            Some(Symbol { value: Value::Nil, .. }) => {
+                self.error("{} is nil, it can not be called!", &[&quote(identifier)])
+            }
+            Some(_) => self.error("{} is not a function!", &[&quote(identifier)]),
+            None if self.natives.contains_key(identifier) => {
+                let mut args: Vec<Value> = vec![];
+                for param in params {
+                    args.push(self.evaluate(param)?);
+                }
+
+                self.call_native(identifier, args)
+            }
+            // Built-ins are called by their names in the language of the program, like 'yaz'
+            // for 'print' in Turkish.
+            None => {
+                match self.language.builtin(identifier) {
+                    Some(builtin) => self.call_builtin(identifier, builtin, params),
+                    None => self.error("{} function not found!", &[&quote(identifier)]),
+                }
+            }
+        }
+    }
+
+    // Call a built-in by its English name, messages have the name that it is called with.
+    fn call_builtin(&mut self,
+                    identifier: &str,
+                    builtin: &str,
+                    params: &[Box<Expr>])
+                    -> Exec<Value> {
+        match builtin {
+            "print" => {
+                self.print(params)?;
+                Ok(Value::Nil)
+            }
+            "read" => {
+                self.get(params)?;
+                Ok(Value::Nil)
+            }
+            "args" => {
+                if !params.is_empty() {
+                    return self.error("{} expects {} parameters but {} given.",
+                                      &[&quote(identifier), "0", &params.len().to_string()]);
+                }
+
+                Ok(Value::Array(self.args.iter().cloned().map(Value::String).collect()))
+            }
+            "exit" => {
+                let mut args: Vec<Value> = vec![];
+                for param in params {
+                    args.push(self.evaluate(param)?);
+                }
+
+                Err(Unwind::Exit(self.exit_status(identifier, &args)?))
+            }
+            _ => {
+                let mut args: Vec<Value> = vec![];
+                for param in params {
+                    args.push(self.evaluate(param)?);
+                }
+
+                let call = Call {
+                    name: identifier,
+                    args: &args,
+                    strict: self.strict,
+                    language: self.language,
+                };
+                match builtins::call(builtin, &call) {
+                    Some(Ok(value)) => Ok(value),
+                    Some(Err(message)) => runtime_error(message),
+                    None => self.error("{} function not found!", &[&quote(identifier)]),
+                }
+            }
+        }
+    }
+
+    // Status of 'exit()' or 'exit(code)', it must be an integer from 0 to 255.
+    fn exit_status(&self, identifier: &str, args: &[Value]) -> Exec<i32> {
+        if args.is_empty() {
+            return Ok(0);
+        } else if args.len() > 1 {
+            return self.error("{} expects {} parameters but {} given.",
+                              &[&quote(identifier), "1", &args.len().to_string()]);
+        }
+
+        match args[0] {
+            Value::Number(x) if x.fract() == 0.0 && x >= 0.0 && x <= 255.0 => Ok(x as i32),
+            Value::Number(x) => {
+                self.error("Exit code must be an integer from 0 to 255, found {}.",
+                           &[&x.to_string()])
+            }
+            ref x => self.error("Exit code must be a number but {} given.", &[&self.type_name(x)]),
+        }
+    }
+
+    fn call_native(&mut self, identifier: &str, args: Vec<Value>) -> Exec<Value> {
+        let (params, function) = {
+            let native = &self.natives[identifier];
+            (native.params.clone(), native.function.clone())
         };
 
-        // Get if condition
-        if let Expr_::Variable(ref x) = identifier.node {
-            variable = self.symbol_table.get(x).unwrap().clone();
+        if args.len() != params.len() {
+            return self.error("{} expects {} parameters but {} given.",
+                              &[&quote(identifier),
+                                &params.len().to_string(),
+                                &args.len().to_string()]);
+        }
+
+        for (i, (param_type, arg)) in params.iter().zip(&args).enumerate() {
+            if !param_type.accepts(&arg.value_type()) {
+                return self.error("Parameter {} of {} must be {} but {} given.",
+                                  &[&(i + 1).to_string(),
+                                    &quote(identifier),
+                                    &self.language.type_name(param_type),
+                                    &self.type_name(arg)]);
+            }
+        }
+
+        match function(&args) {
+            Ok(value) => Ok(value),
+            Err(message) => runtime_error(message),
+        }
+    }
+
+    fn call_function(&mut self,
+                     identifier: &str,
+                     function: &Function,
+                     args: Vec<Value>)
+                     -> Exec<Value> {
+        if args.len() != function.params.len() {
+            return self.error("{} expects {} parameters but {} given.",
+                              &[&quote(identifier),
+                                &function.params.len().to_string(),
+                                &args.len().to_string()]);
         }
 
-        // If condition is a bool value interpret if, otherwise display an error.
-        match variable.value {
-            Constant::Bool(x) => {
+        // Parameters live in a new scope inside the scope function is created in.
+        let scope = Environment::new(Some(function.closure.clone()));
+        for (param, arg) in function.params.iter().zip(args) {
+            if !param.param_type.accepts(&arg.value_type()) {
+                return self.error("Parameter {} of {} must be {} but {} given.",
+                                  &[&quote(&param.name),
+                                    &quote(identifier),
+                                    &self.language.type_name(&param.param_type),
+                                    &self.type_name(&arg)]);
+            }
+
+            scope.borrow_mut().define(&param.name, param.param_type.clone(), arg);
+        }
+
+        if self.call_depth == self.max_call_depth {
+            return self.error("Maximum call depth of {} is exceeded while calling {}!",
+                              &[&self.max_call_depth.to_string(), &quote(identifier)]);
+        }
+
+        let previous = mem::replace(&mut self.env, scope);
+        self.call_depth += 1;
+        let result = self.run_block(&function.body);
+        self.call_depth -= 1;
+        self.env = previous;
+
+        match result {
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn interpret_if(&mut self,
+                    condition: &Expr,
+                    if_block: &Expr,
+                    else_block: &Option<Box<Expr>>)
+                    -> Exec<()> {
+        // If condition is a bool value interpret if, otherwise give an error.
+        match self.evaluate(condition)? {
+            Value::Bool(x) => {
                 // If bool value is true then execute if block.
                 if x {
-                    let _if = if_block.node.clone();
-                    self.run_block(_if);
+                    self.run_block(if_block)
 
                     // If bool value is false and else block is exist, execute else block.
                 } else if let Some(ref block) = *else_block {
-                    {
-                        let _else = block.node.clone();
-                        self.run_block(_else);
-                    }
+                    self.run_block(block)
+                } else {
+                    Ok(())
                 }
             }
-            Constant::String(_) => panic!("Uninitilized variable found!"),
-            _ => unimplemented!(),
+            Value::Nil => self.error("If condition is nil, a bool value is needed!", &[]),
+            x => {
+                self.error("If condition must be a bool value, found {}.",
+                           &[&self.type_name(&x)])
+            }
         }
     }
 
-    fn print(&mut self, params: Vec<Box<Expr>>) {
+    // Evaluate and join the values, used by 'print' and string interpolation.
+    fn concat(&mut self, params: &[Box<Expr>]) -> Exec<String> {
         let mut output: String = "".to_string();
 
         for param in params {
-            match param.node {
-                Expr_::Constant(ref constant) => {
-                    match *constant {
-                        Constant::String(ref x) => output.push_str(x),
-                        Constant::Number(ref x) => output.push_str(&x.to_string()),
-                        Constant::Bool(ref x) => output.push_str(&x.to_string()),
-                    };
-                }
-
-                Expr_::Variable(ref var) => {
-                    match self.symbol_table.get(var) {
-                        Some(variable) => output.push_str(&format!("{:?}", &variable.value)),
-                        None => println!("{:?} variable not found!", var),
+            output.push_str(&self.evaluate(param)?.to_string());
+        }
+
+        Ok(output)
+    }
+
+    fn print(&mut self, params: &[Box<Expr>]) -> Exec<()> {
+        let output = self.concat(params)?;
+
+        match writeln!(self.output, "{}", output) {
+            Ok(()) => Ok(()),
+            Err(error) => self.error("Could not write output: {}", &[&error.to_string()]),
+        }
+    }
+
+    // Read input into the variables, each value is parsed with the declared type of its
+    // variable. Numbers, bools and chars are whitespace separated words which can be on the
+    // same or different lines, strings take the rest of the current line or the next line.
+    fn get(&mut self, params: &[Box<Expr>]) -> Exec<()> {
+        for param in params {
+            let var = match param.node {
+                Expr_::Variable(ref var) => var,
+                _ => return self.error("Parameter requires a variable identifier!", &[]),
+            };
+
+            // Undeclared variables are created as strings.
+            let declared_type = match self.env.borrow().get(var) {
+                Some(symbol) => Some(symbol.declared_type),
+                None => None,
+            };
+            let input_type = match declared_type {
+                Some(Type::Optional(ref inner)) => (**inner).clone(),
+                Some(ref x) => x.clone(),
+                None => Type::String,
+            };
+
+            let input = match input_type {
+                Type::String => self.read_input_line()?,
+                Type::Number | Type::Bool | Type::Char => self.read_input_word()?,
+                ref x => {
+                    return self.error("{} is a {} variable, it can not store input.",
+                                      &[&quote(var), &self.language.type_name(x)])
+                }
+            };
+
+            let input = match input {
+                Some(x) => x,
+                None => {
+                    return self.error("End of input reached while reading {}!", &[&quote(var)])
+                }
+            };
+
+            let value = match input_type {
+                Type::Number => {
+                    match input.parse::<f64>() {
+                        Ok(x) if x.is_finite() => Some(Value::Number(x)),
+                        _ => None,
                     }
                 }
-                _ => println!("Other type of node found!"),
+                Type::Bool => {
+                    match &*input {
+                        "true" => Some(Value::Bool(true)),
+                        "false" => Some(Value::Bool(false)),
+                        _ => None,
+                    }
+                }
+                Type::Char if input.chars().count() == 1 => {
+                    Some(Value::Char(input.chars().next().unwrap()))
+                }
+                Type::Char => None,
+                _ => Some(Value::String(input.clone())),
+            };
+
+            match value {
+                Some(value) => {
+                    if declared_type.is_some() {
+                        self.env.borrow_mut().assign(var, value);
+                    } else {
+                        self.env.borrow_mut().define(var, Type::String, value);
+                    }
+                }
+                None => {
+                    return self.error("Expected a {} value for {} but {} found in input!",
+                                      &[&self.language.type_name(&input_type),
+                                        &quote(var),
+                                        &quote(&input)])
+                }
             }
         }
 
-        println!("{}", output);
+        Ok(())
     }
 
-    fn get(&mut self, params: Vec<Box<Expr>>) {
-        for param in params {
-            let stdin = io::stdin();
-            let line = stdin.lock().lines().next().unwrap().unwrap();
+    // Next whitespace separated word of the input, None at the end of input.
+    fn read_input_word(&mut self) -> Exec<Option<String>> {
+        loop {
+            let word = {
+                let rest = self.input_line.trim_start();
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (rest[..end].to_string(), rest[end..].to_string())
+            };
 
-            match param.node {
-                Expr_::Variable(ref var) => {
-                    self.symbol_table.insert(var.to_owned(),
-                                             Symbol {
-                                                 symbol_type: SymbolType::Variable,
-                                                 value: Constant::String(line.clone()),
-                                             });
+            if !word.0.is_empty() {
+                self.input_line = word.1;
+                return Ok(Some(word.0));
+            }
+
+            self.input_line = match self.next_input_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            };
+        }
+    }
+
+    // Unread rest of the current line if there is any, otherwise the next line of the input.
+    fn read_input_line(&mut self) -> Exec<Option<String>> {
+        let rest = mem::replace(&mut self.input_line, "".to_string());
+
+        if rest.trim().is_empty() {
+            self.next_input_line()
+        } else {
+            Ok(Some(rest.trim_start().to_string()))
+        }
+    }
+
+    fn next_input_line(&mut self) -> Exec<Option<String>> {
+        // Show the output written so far, like a question, before waiting for the input.
+        let _ = self.output.flush();
+        let mut line = "".to_string();
+
+        match self.input.read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                // Remove the line break
+                while line.ends_with('\n') || line.ends_with('\r') {
+                    line.pop();
                 }
-                _ => println!("Parameter requires a variable identifier!"),
+
+                Ok(Some(line))
             }
+            Err(error) => self.error("Could not read input: {}", &[&error.to_string()]),
         }
     }
 }
@@ -0,0 +1,249 @@
+// Interpreter for Basic C like language
+// Language Module
+//
+
+use ast::Type;
+use lexer::{keyword, TokenType};
+
+// Language of the keywords, built-in names and messages. A file can select it with a pragma on
+// its first line, or after its shebang line, like '// language: turkish' or '// dil: türkçe'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Turkish,
+}
+
+impl Default for Language {
+    fn default() -> Language {
+        Language::English
+    }
+}
+
+// Built-in functions and constants, the first name is English and the second one is Turkish.
+// A program calls them by the names in its language, the interpreter knows them by their
+// English names.
+static BUILTINS: &'static [(&'static str, &'static str)] = &[
+    ("print", "yaz"),
+    ("read", "oku"),
+    ("args", "argümanlar"),
+    ("exit", "çıkış"),
+    ("env", "ortam"),
+    ("ord", "kod"),
+    ("chr", "koddan"),
+    ("toString", "metne"),
+    ("toChar", "karaktere"),
+    ("toNumber", "sayıya"),
+    ("toBool", "mantıksala"),
+    ("typeof", "türü"),
+    ("length", "uzunluk"),
+    ("substring", "altMetin"),
+    ("find", "bul"),
+    ("replace", "değiştir"),
+    ("split", "böl"),
+    ("join", "birleştir"),
+    ("trim", "kırp"),
+    ("upper", "büyükHarf"),
+    ("lower", "küçükHarf"),
+    ("startsWith", "ileBaşlar"),
+    ("endsWith", "ileBiter"),
+    ("repeat", "tekrarla"),
+    ("abs", "mutlak"),
+    ("floor", "aşağıYuvarla"),
+    ("ceil", "yukarıYuvarla"),
+    ("round", "yuvarla"),
+    ("sqrt", "karekök"),
+    ("sin", "sin"),
+    ("cos", "cos"),
+    ("tan", "tan"),
+    ("asin", "asin"),
+    ("acos", "acos"),
+    ("atan", "atan"),
+    ("atan2", "atan2"),
+    ("exp", "exp"),
+    ("pow", "kuvvet"),
+    ("log", "log"),
+    ("min", "enKüçük"),
+    ("max", "enBüyük"),
+    ("PI", "PI"),
+    ("E", "E"),
+];
+
+// Names of the built-ins in the older versions, English programs of them are run in the legacy
+// case-insensitive mode.
+static LEGACY_BUILTINS: &'static [(&'static str, &'static str)] = &[
+    ("yaz", "print"),
+    ("oku", "read"),
+];
+
+// Messages of the lexer, parser, checker and interpreter, English ones are the keys. '{}' in a
+// message is replaced with an argument.
+static MESSAGES: &'static [(&'static str, &'static str)] = &[
+    ("{} at line {}, column {}!", "{}, satır {}, sütun {}!"),
+    ("Syntax error: {}", "Sözdizimi hatası: {}"),
+    ("Parse error: {}", "Ayrıştırma hatası: {}"),
+    ("Type error: {}", "Tür hatası: {}"),
+    ("Runtime error: {}", "Çalışma hatası: {}"),
+    ("Unexpected token: {}", "Beklenmeyen karakter: {}"),
+    ("The code is not valid UTF-8, it must be saved as UTF-8",
+     "Kod geçerli UTF-8 değil, UTF-8 olarak kaydedilmeli"),
+    ("Invalid UTF-16 in the code", "Kodda geçersiz UTF-16 var"),
+    ("Could not read the code: {}", "Kod okunamadı: {}"),
+    ("Unknown language: {}", "Bilinmeyen dil: {}"),
+    ("Unterminated block comment", "Kapatılmamış blok yorumu"),
+    ("Invalid number literal: {}", "Geçersiz sayı: {}"),
+    ("Unterminated string literal", "Kapatılmamış metin"),
+    ("Unterminated char literal", "Kapatılmamış karakter"),
+    ("Empty char literal", "Boş karakter"),
+    ("Unterminated escape sequence", "Kapatılmamış kaçış dizisi"),
+    ("Expected '{' in unicode escape", "Unicode kaçış dizisinde '{' bekleniyor"),
+    ("Unterminated unicode escape", "Kapatılmamış unicode kaçış dizisi"),
+    ("Invalid unicode escape '\\u{{}}'", "Geçersiz unicode kaçış dizisi '\\u{{}}'"),
+    ("Unknown escape sequence '\\{}'", "Bilinmeyen kaçış dizisi '\\{}'"),
+    ("Unterminated string interpolation", "Kapatılmamış metin içi ifade"),
+    ("Empty string interpolation", "Boş metin içi ifade"),
+    ("Unexpected token found. Expected: {}, Found: {} instead.",
+     "Beklenmeyen simge. Beklenen: {}, bulunan: {}."),
+    ("Unexpected token found: {}.", "Beklenmeyen simge: {}."),
+    ("Keyword '{}' can't be used here.", "'{}' anahtar kelimesi burada kullanılamaz."),
+    ("Error while parsing to number.", "Sayı okunurken hata oluştu."),
+    ("Error while reading type.", "Tür okunurken hata oluştu."),
+    ("Error while reading identifier.", "İsim okunurken hata oluştu."),
+    ("{} is declared without a value, it needs an optional type like '{}?'.",
+     "{} değer verilmeden tanımlandı, '{}?' gibi isteğe bağlı bir tür gerekiyor."),
+    ("Parse error in arithmetic expression. Check the operands.",
+     "Aritmetik ifadede hata var. İşlenenleri kontrol edin."),
+    ("Expression expected.", "İfade bekleniyor."),
+    ("Unexpected end of file. Expected: \"RBrace\".",
+     "Beklenmeyen dosya sonu. Beklenen: \"RBrace\"."),
+    ("{} variable not found!", "{} değişkeni bulunamadı!"),
+    ("{} function not found!", "{} fonksiyonu bulunamadı!"),
+    ("{} is not a function!", "{} bir fonksiyon değil!"),
+    ("{} is nil, it can not be called!", "{} boş, çağrılamaz!"),
+    ("{} can't be declared as nil, an optional type is needed.",
+     "{} boş olarak tanımlanamaz, isteğe bağlı bir tür gerekiyor."),
+    ("{} is declared as {} but assigned a {} value.",
+     "{} {} olarak tanımlandı ama {} değeri atandı."),
+    ("{} is a {} variable but assigned a {} value.",
+     "{} bir {} değişkeni ama {} değeri atandı."),
+    ("{} expects {} parameters but {} given.", "{} {} parametre bekliyor ama {} verildi."),
+    ("{} expects at least 1 parameter but 0 given.",
+     "{} en az 1 parametre bekliyor ama 0 verildi."),
+    ("{} expects a {} parameter but {} given.", "{} bir {} parametre bekliyor ama {} verildi."),
+    ("Parameter {} of {} must be {} but {} given.",
+     "{} parametresi, {} fonksiyonunda {} olmalı ama {} verildi."),
+    ("Maximum call depth of {} is exceeded while calling {}!",
+     "En fazla {} iç içe çağrı sınırı {} çağrılırken aşıldı!"),
+    ("Nil value found where a concrete value is needed!",
+     "Somut bir değer gereken yerde boş değer bulundu!"),
+    ("Nil value found where a number is needed!", "Sayı gereken yerde boş değer bulundu!"),
+    ("Cannot add {} and {} values.", "{} ve {} değerleri toplanamaz."),
+    ("Cannot negate a {} value.", "{} değerinin negatifi alınamaz."),
+    ("Arithmetic requires number values, found {} and {}.",
+     "Aritmetik işlem sayı değerleri gerektirir, {} ve {} bulundu."),
+    ("Cannot compare {} and {} values.", "{} ve {} değerleri karşılaştırılamaz."),
+    ("Cannot index a {} value with a {} value.", "{} değeri {} değeriyle indekslenemez."),
+    ("Unexpected expression found!", "Beklenmeyen ifade bulundu!"),
+    ("If condition is nil, a bool value is needed!",
+     "Eğer koşulu boş, mantıksal bir değer gerekiyor!"),
+    ("If condition must be a bool value, found {}.",
+     "Eğer koşulu mantıksal bir değer olmalı, {} bulundu."),
+    ("Parameter requires a variable identifier!", "Parametre bir değişken ismi olmalı!"),
+    ("{} is a {} variable, it can not store input.", "{} bir {} değişkeni, girdi saklayamaz."),
+    ("End of input reached while reading {}!", "{} okunurken girdinin sonuna gelindi!"),
+    ("Expected a {} value for {} but {} found in input!",
+     "{} değeri bekleniyordu, {} için girdide {} bulundu!"),
+    ("Could not write output: {}", "Çıktı yazılamadı: {}"),
+    ("Could not read input: {}", "Girdi okunamadı: {}"),
+    ("Exit code must be an integer from 0 to 255, found {}.",
+     "Çıkış kodu 0 ile 255 arasında bir tam sayı olmalı, {} bulundu."),
+    ("Exit code must be a number but {} given.", "Çıkış kodu sayı olmalı ama {} verildi."),
+    ("{} is not a valid index!", "{} geçerli bir indeks değil!"),
+    ("Index {} is out of range for length {}!", "{} indeksi {} uzunluğu için aralık dışında!"),
+    ("Start index {} is greater than end index {}!",
+     "Başlangıç indeksi {}, bitiş indeksi {} değerinden büyük!"),
+    ("{} is not a valid code point!", "{} geçerli bir kod noktası değil!"),
+    ("{} must have exactly one character to be a char!",
+     "{} karakter olması için tam olarak bir karakter içermeli!"),
+    ("{} can not replace an empty string!", "{} boş bir metni değiştiremez!"),
+    ("{} separator can not be empty!", "{} ayıracı boş olamaz!"),
+    ("{} count must be a non-negative integer, found {}!",
+     "{} tekrar sayısı negatif olmayan bir tam sayı olmalı, {} bulundu!"),
+    ("{} result is too long, it can be at most {} bytes!",
+     "{} sonucu çok uzun, en fazla {} bayt olabilir!"),
+    ("{} is not defined for {}!", "{}, {} için tanımlı değil!"),
+    ("Program has ended.", "Program sona erdi."),
+    ("Unimplemented feature found!", "Desteklenmeyen bir özellik bulundu!"),
+    ("Block not found!", "Blok bulunamadı!"),
+];
+
+impl Language {
+    // Language for its name or code like "turkish", "türkçe" or "tr".
+    pub fn from_name(name: &str) -> Option<Language> {
+        match &*name.to_lowercase() {
+            "en" | "english" | "ingilizce" => Some(Language::English),
+            "tr" | "turkish" | "türkçe" | "turkce" => Some(Language::Turkish),
+            _ => None,
+        }
+    }
+
+    // English name of the built-in function or constant that is called by the given name in
+    // this language, like "print" for "yaz" in Turkish. Names of the other language are not
+    // built-ins, they can be used as any other name.
+    pub fn builtin(self, name: &str) -> Option<&'static str> {
+        BUILTINS.iter()
+            .find(|&&(english, turkish)| self.choose(english, turkish) == name)
+            .map(|&(english, _)| english)
+    }
+
+    // Name of the built-in which is written as the given word in lowercase, like 'toString'
+    // for 'tostring'. The legacy case-insensitive lexer uses it for the lowercased words of the
+    // older programs, their 'yaz' and 'oku' are 'print' and 'read' in English.
+    pub fn lowercase_builtin(self, word: &str) -> Option<&'static str> {
+        let legacy = match self {
+            Language::English => LEGACY_BUILTINS.iter().find(|x| x.0 == word).map(|x| x.1),
+            Language::Turkish => None,
+        };
+
+        legacy.or_else(|| {
+            BUILTINS.iter()
+                .map(|&(english, turkish)| self.choose(english, turkish))
+                .find(|x| x.to_lowercase() == word)
+        })
+    }
+
+    // Name of the type in this language like "sayı?" for 'number?' in Turkish.
+    pub fn type_name(self, value_type: &Type) -> String {
+        let token_type = match *value_type {
+            Type::Function => TokenType::Fn,
+            Type::Nil => TokenType::Nil,
+            Type::Optional(ref inner) => return format!("{}?", self.type_name(inner)),
+            ref x => TokenType::Type(x.clone()),
+        };
+
+        keyword(&token_type, self).map_or_else(|| value_type.to_string(), str::to_string)
+    }
+
+    // Message in this language, each '{}' in it is replaced with the next argument.
+    pub fn message(self, message: &str, args: &[&str]) -> String {
+        let template = MESSAGES.iter()
+            .find(|&&(english, _)| english == message)
+            .map_or(message, |&(english, turkish)| self.choose(english, turkish));
+
+        let mut parts = template.split("{}");
+        let mut result = parts.next().unwrap_or("").to_string();
+        for (i, part) in parts.enumerate() {
+            result.push_str(args.get(i).map_or("{}", |x| *x));
+            result.push_str(part);
+        }
+
+        result
+    }
+
+    // Word of the two that is in this language, the first one is English.
+    pub fn choose(self, english: &'static str, turkish: &'static str) -> &'static str {
+        match self {
+            Language::English => english,
+            Language::Turkish => turkish,
+        }
+    }
+}
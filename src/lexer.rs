@@ -2,73 +2,133 @@
 // Lexer Module
 //
 
+use std::char;
+use std::cmp;
+use std::collections::VecDeque;
 use std::fmt;
+use std::io::{self, BufRead, Read};
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub enum TokenType {
-    Keyword(String), // like int, string or let
-    Identifier(String), // like variable names
-    Char(String), // Char variables, inside " ' "
-    String(String), // String variables, inside quotes
-    Number(String), // Number variable
-    True, // Boolean true
-    False, // Boolean false
-    Equals, // =
-    Plus, // +
-    Minus, // -
-    Multiple, // *
-    Divide, // /
-    Mod, // %
-    Greater, // >
-    Lesser, // <
-    GreaterEqual, // >=
-    LesserEqual, // <=
-    LParen, // (
-    RParen, // )
-    LBrace, // {
-    RBrace, // }
-    LBracket, // [
-    RBracket, // ]
-    Comma, // ,
-    Semicolon, // ;
-    Comment, // '//'
-    EOF, // End of File
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
+use ast::Type;
+use language::Language;
+
+// Token types and their kinds are generated from the lists below, so a keyword is added with
+// one line. Keywords are written with their English and Turkish words, type names are the
+// keywords of 'TokenType::Type'.
+macro_rules! token_types {
+    (
+        keywords { $($keyword:ident: ($keyword_english:expr, $keyword_turkish:expr),)* }
+        types { $($type_name:ident: ($type_english:expr, $type_turkish:expr),)* }
+        values { $($value:ident($value_type:ty),)* }
+        symbols { $($symbol:ident,)* }
+    ) => {
+        #[derive(Clone, PartialEq, Eq, Hash)]
+        pub enum TokenType {
+            $($keyword,)*
+            Type(Type),
+            $($value($value_type),)*
+            $($symbol,)*
+        }
+
+        // Kind of a token without its value, parser matches the tokens with it.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum TokenKind {
+            $($keyword,)*
+            Type,
+            $($value,)*
+            $($symbol,)*
+        }
+
+        impl TokenType {
+            pub fn kind(&self) -> TokenKind {
+                match *self {
+                    $(TokenType::$keyword => TokenKind::$keyword,)*
+                    TokenType::Type(_) => TokenKind::Type,
+                    $(TokenType::$value(_) => TokenKind::$value,)*
+                    $(TokenType::$symbol => TokenKind::$symbol,)*
+                }
+            }
+        }
+
+        // Keywords and their tokens, the first word is English and the second one is
+        // Turkish. These words can't be used as names.
+        static KEYWORDS: &'static [(TokenType, &'static str, &'static str)] = &[
+            $((TokenType::$keyword, $keyword_english, $keyword_turkish),)*
+            $((TokenType::Type(Type::$type_name), $type_english, $type_turkish),)*
+        ];
+    }
+}
+
+token_types! {
+    keywords {
+        Main: ("main", "ana"),
+        If: ("if", "eğer"),
+        Else: ("else", "değilse"),
+        While: ("while", "iken"), // Reserved for loops
+        Return: ("return", "döndür"),
+        Fn: ("fn", "fonksiyon"), // Type of functions and start of anonymous functions
+        True: ("true", "doğru"), // Boolean true
+        False: ("false", "yanlış"), // Boolean false
+        Nil: ("nil", "boş"), // Null value
+    }
+    types {
+        Number: ("number", "sayı"),
+        String: ("string", "metin"),
+        Char: ("char", "karakter"),
+        Bool: ("bool", "mantıksal"),
+        Array: ("array", "dizi"),
+    }
+    values {
+        Identifier(String), // like variable names
+        Char(String), // Char variables, inside " ' "
+        String(String), // String variables, inside quotes
+        Template(Vec<TemplatePart>), // String variables with embedded expressions like "${x}"
+        Number(String), // Number literal without the separators like '1000', '2.5e-3' or '0xff'
+    }
+    symbols {
+        Equals, // =
+        EqualEqual, // ==
+        NotEqual, // !=
+        Plus, // +
+        Minus, // -
+        Multiple, // *
+        Divide, // /
+        Mod, // %
+        Greater, // >
+        Lesser, // <
+        GreaterEqual, // >=
+        LesserEqual, // <=
+        LParen, // (
+        RParen, // )
+        LBrace, // {
+        RBrace, // }
+        LBracket, // [
+        RBracket, // ]
+        Comma, // ,
+        Semicolon, // ;
+        Question, // ?
+        Coalesce, // ??
+        Comment, // '// x' or '/* x */', block comments can be nested
+        EOF, // End of File
+    }
 }
 
+// Tokens are shown with the name of their kind, like 'Identifier'.
 impl fmt::Debug for TokenType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            TokenType::Keyword(_) => write!(f, "Keyword"),
-            TokenType::Identifier(_) => write!(f, "Identifier"),
-            TokenType::Char(_) => write!(f, "Char"),
-            TokenType::String(_) => write!(f, "String"),
-            TokenType::Number(_) => write!(f, "Number"),
-            TokenType::True => write!(f, "True"),
-            TokenType::False => write!(f, "False"),
-            TokenType::Equals => write!(f, "Equals"),
-            TokenType::Plus => write!(f, "Plus"),
-            TokenType::Minus => write!(f, "Minus"),
-            TokenType::Multiple => write!(f, "Multiple"),
-            TokenType::Divide => write!(f, "Divide"),
-            TokenType::Mod => write!(f, "Mod"),
-            TokenType::Greater => write!(f, "Greater"),
-            TokenType::Lesser => write!(f, "Lesser"),
-            TokenType::GreaterEqual => write!(f, "GreaterEqual"),
-            TokenType::LesserEqual => write!(f, "LesserEqual"),
-            TokenType::LParen => write!(f, "LParen"),
-            TokenType::RParen => write!(f, "RParen"),
-            TokenType::LBrace => write!(f, "LBrace"),
-            TokenType::RBrace => write!(f, "RBrace"),
-            TokenType::LBracket => write!(f, "LBracket"),
-            TokenType::RBracket => write!(f, "RBracket"),
-            TokenType::Comma => write!(f, "Comma"),
-            TokenType::Semicolon => write!(f, "Semicolon"),
-            TokenType::Comment => write!(f, "Comment"),
-            TokenType::EOF => write!(f, "EOF"),
-        }
+        write!(f, "{:?}", self.kind())
     }
 }
 
+// Part of a string with embedded expressions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TemplatePart {
+    Literal(String), // Plain text
+    Code(String), // Source code of an embedded expression inside "${ }"
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Span {
     pub lo: usize,
@@ -79,341 +139,1298 @@ pub struct Span {
 pub struct Token {
     pub token_type: TokenType,
     pub span: Option<Span>,
+    pub trivia: Vec<Trivia>, // Doc comments before the token, all the trivia if it is kept
+    pub text: Option<String>, // Source text of the token if trivia is kept
 }
 
+impl Token {
+    // Text of the doc comments before the token, lines are joined with line breaks.
+    pub fn doc(&self) -> Option<String> {
+        let lines = self.trivia
+            .iter()
+            .filter_map(|x| match *x {
+                Trivia::DocComment(ref text) => {
+                    let line = &text[3..];
+                    Some(if line.starts_with(' ') { &line[1..] } else { line })
+                }
+                _ => None,
+            })
+            .collect::<Vec<&str>>();
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+// Source text between tokens. Text of the trivia before each token and the text of the token
+// give the code back exactly, the trivia at the end of the code is before EOF.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    Whitespace(String),
+    Comment(String), // '// x' or '/* x */' with its delimiters
+    DocComment(String), // '/// x' with its delimiter, it documents the next declaration
+}
+
+// Error found while tokenizing the code, line and column start from 1
 #[derive(Debug, Clone)]
+pub struct LexError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub language: Language, // Language of the message
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line = self.line.to_string();
+        let column = self.column.to_string();
+        let args: [&str; 3] = [&self.message, &line, &column];
+        write!(f, "{}", self.language.message("{} at line {}, column {}!", &args))
+    }
+}
+
+// Settings of the lexer, given when it is constructed. Default is the case-sensitive language.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexerOptions {
+    // Legacy mode of the older versions: words are lowercased, so 'Count' and 'count' are the
+    // same variable and 'TRUE' is a boolean.
+    pub case_insensitive: bool,
+    // Keep the whitespace and comments as the trivia of the tokens, with the text of each
+    // token, for tools like formatters. Comments are not tokens then.
+    pub keep_trivia: bool,
+    // Language of the keywords and the messages, a pragma on the first line of the code like
+    // '// language: turkish' changes it.
+    pub language: Language,
+}
+
+// Tokens of the code for the parser. Tokens are read from the lexer when they are needed and
+// only the ones that are looked ahead are kept. Comments are skipped.
 pub struct TokenStream {
-    pub code: String,
-    pub tokens: Vec<Token>,
-    pub pos: usize,
-    pub curr: Option<char>,
+    lexer: Lexer,
+    lookahead: VecDeque<Token>,
+    error: Option<LexError>, // Error of the lexer, tokens after it are EOF
 }
 
 impl TokenStream {
-    pub fn new(_code: String) -> TokenStream {
-        let mut new_token_stream = TokenStream {
-            code: _code,
-            tokens: vec![],
-            pos: 0,
-            curr: None,
-        };
+    pub fn new(code: String) -> TokenStream {
+        TokenStream::from_lexer(Lexer::new(code))
+    }
 
-        new_token_stream.tokenize();
-        new_token_stream
+    pub fn from_reader<R: BufRead + 'static>(reader: R) -> TokenStream {
+        TokenStream::from_lexer(Lexer::from_reader(reader))
     }
 
-    #[allow(cyclomatic_complexity)]
-    pub fn tokenize(&mut self) {
-        let mut tokens: Vec<Token> = vec![];
-        let char_count = self.code.chars().count();
-        let mut i = 0;
+    pub fn from_lexer(lexer: Lexer) -> TokenStream {
+        TokenStream {
+            lexer: lexer,
+            lookahead: VecDeque::new(),
+            error: None,
+        }
+    }
 
-        while i < char_count {
-            let current_char = self.nth_char(i);
+    pub fn options(&self) -> LexerOptions {
+        self.lexer.options()
+    }
 
-            // If char is whitespace, just pass the current char
-            if current_char.is_whitespace() {
-                i += 1;
-            }
-            // If char starts with alphabetic characters
-            else if current_char.is_alphabetic() {
-                let mut tmp = "".to_string();
+    // Token that is 'n' tokens ahead, 0 is the next token.
+    pub fn peek(&mut self, n: usize) -> &Token {
+        while self.lookahead.len() <= n {
+            let token = self.read_token();
+            self.lookahead.push_back(token);
+        }
 
-                // If current char is not out of our code scope and char starts with
-                // alphanumeric (alphabetic or numeric) characters
-                while i < char_count && self.nth_char(i).is_alphanumeric() {
+        &self.lookahead[n]
+    }
 
-                    tmp = tmp + &*self.nth_char(i).to_string();
-                    i += 1;
-                }
+    pub fn next_token(&mut self) -> Token {
+        self.peek(0);
+        self.lookahead.pop_front().unwrap()
+    }
 
-                // We have the word, now we need to find what it is and tokenize it.
-                let tmp_str = tmp.to_lowercase();
-                if self.is_keyword(&tmp_str) {
-                    tokens.push(Token {
-                        token_type: TokenType::Keyword(tmp_str),
-                        span: None,
-                    });
-                } else if tmp_str == "true" {
-                    tokens.push(Token {
-                        token_type: TokenType::True,
-                        span: None,
-                    });
-                } else if tmp_str == "false" {
-                    tokens.push(Token {
-                        token_type: TokenType::False,
-                        span: None,
-                    });
-                } else {
-                    tokens.push(Token {
-                        token_type: TokenType::Identifier(tmp_str),
-                        span: None,
-                    });
+    pub fn error(&self) -> Option<&LexError> {
+        self.error.as_ref()
+    }
+
+    fn read_token(&mut self) -> Token {
+        // Doc comments before a skipped comment belong to the next token.
+        let mut trivia = vec![];
+
+        loop {
+            match self.lexer.next() {
+                Some(Ok(Token { token_type: TokenType::Comment, trivia: x, .. })) => {
+                    trivia.extend(x);
+                    continue;
+                }
+                Some(Ok(mut token)) => {
+                    trivia.extend(token.trivia);
+                    token.trivia = trivia;
+                    return token;
                 }
+                Some(Err(error)) => self.error = Some(error),
+                None => {}
             }
-            // If current char is a numerical character
-            else if current_char.is_numeric() {
-                let mut tmp = "".to_string();
 
-                while i < char_count && self.nth_char(i).is_numeric() {
-                    tmp = tmp + &*self.nth_char(i).to_string();
-                    i += 1;
-                }
+            // End of the code, it is repeated for the parser to see.
+            return Token {
+                token_type: TokenType::EOF,
+                span: None,
+                trivia: vec![],
+                text: None,
+            };
+        }
+    }
+}
+
+// Position of a char in the code, offset is in bytes, line and column are in chars
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+// Lexer reads the code from its reader line by line when it is needed and visits each char
+// once, so the time of tokenizing grows linearly with the length of the code. Tokens are
+// produced one by one, the last one is EOF.
+pub struct Lexer {
+    reader: Box<dyn BufRead>,
+    buffer: String, // Code that is read but not tokenized yet
+    pos: usize, // Byte offset of the next char in the buffer
+    position: Position, // Position of the next char in the whole code
+    read_error: Option<LexError>, // Error while reading the code, it ends the code
+    finished: bool,
+    started: bool, // Start of the code like a byte order mark or a shebang line is passed
+    first_line: usize, // Line of the language pragma, it is after the shebang line
+    options: LexerOptions,
+}
+
+impl Iterator for Lexer {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Result<Token, LexError>> {
+        if self.finished {
+            return None;
+        }
+
+        // Comments are looked for a few chars ahead, that may read the next line and fail
+        // after a complete token. Error of reading is given when the code ends because of it.
+        let result = match self.read_token() {
+            Ok(Token { token_type: TokenType::EOF, .. }) |
+            Err(_) if self.read_error.is_some() => Err(self.read_error.take().unwrap()),
+            result => result,
+        };
+
+        match result {
+            Ok(Token { token_type: TokenType::EOF, .. }) |
+            Err(_) => self.finished = true,
+            _ => {}
+        }
+
+        Some(result)
+    }
+}
+
+impl Lexer {
+    pub fn new(code: String) -> Lexer {
+        Lexer::from_reader(io::Cursor::new(code.into_bytes()))
+    }
+
+    pub fn from_reader<R: BufRead + 'static>(reader: R) -> Lexer {
+        Lexer::with_options(reader, LexerOptions::default())
+    }
+
+    pub fn with_options<R: BufRead + 'static>(reader: R, options: LexerOptions) -> Lexer {
+        Lexer {
+            reader: Box::new(reader),
+            buffer: String::new(),
+            pos: 0,
+            position: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            read_error: None,
+            finished: false,
+            started: false,
+            first_line: 1,
+            options: options,
+        }
+    }
+
+    pub fn options(&self) -> LexerOptions {
+        self.options
+    }
+
+    fn read_token(&mut self) -> Result<Token, LexError> {
+        let mut trivia = vec![];
+        if !self.started {
+            self.started = true;
+            trivia = self.read_start()?;
+        }
+        trivia.extend(self.read_trivia()?);
+
+        // Tokenized part of the buffer is not needed anymore.
+        if self.pos == self.buffer.len() || self.pos >= 4096 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        let start = self.position;
+        let from = self.pos;
+        let token_type = self.read_token_type(start)?;
+
+        let mut token = self.token(token_type, start);
+        token.trivia = trivia;
+        if self.options.keep_trivia {
+            token.text = Some(self.buffer[from..self.pos].to_string());
+        }
+
+        Ok(token)
+    }
+
+    // Pass the byte order mark and the shebang line like '#!/usr/bin/env interpreter' at the
+    // start of the code, they are trivia. Code with a UTF-16 byte order mark is decoded first.
+    fn read_start(&mut self) -> Result<Vec<Trivia>, LexError> {
+        let mut trivia = vec![];
+        self.decode_utf16()?;
 
-                tokens.push(Token {
-                    token_type: TokenType::Number(tmp),
-                    span: None,
-                });
+        // Byte order mark is not a char of the code, so the columns don't count it.
+        if self.peek() == Some('\u{feff}') {
+            self.pos += '\u{feff}'.len_utf8();
+            self.position.offset += '\u{feff}'.len_utf8();
+            if self.options.keep_trivia {
+                trivia.push(Trivia::Whitespace("\u{feff}".to_string()));
             }
-            // If current char is a starting of a string
-            else if current_char == '"' {
-                let mut tmp = "".to_string();
-                i += 1;
+        }
 
-                while i < char_count && self.nth_char(i) != '"' {
-                    tmp = tmp + &*self.nth_char(i).to_string();
-                    i += 1;
-                }
+        if self.starts_with("#!") {
+            let shebang = self.eat_while(|x| x != '\n');
+            if self.options.keep_trivia {
+                trivia.push(Trivia::Comment(shebang));
+            }
+            self.first_line = 2;
+        }
+
+        Ok(trivia)
+    }
+
+    // Code that starts with a UTF-16 byte order mark is read as a whole and decoded to UTF-8,
+    // offsets of the spans are in the decoded code then.
+    fn decode_utf16(&mut self) -> Result<(), LexError> {
+        let big_endian = match self.reader.fill_buf() {
+            Ok(bytes) if bytes.starts_with(&[0xFF, 0xFE]) => false,
+            Ok(bytes) if bytes.starts_with(&[0xFE, 0xFF]) => true,
+            _ => return Ok(()),
+        };
+
+        let start = self.position;
+        let mut bytes = vec![];
+        if let Err(error) = self.reader.read_to_end(&mut bytes) {
+            let error = error.to_string();
+            return Err(self.lex_error("Could not read the code: {}", &[&error], start));
+        }
+
+        if bytes.len() % 2 != 0 {
+            return Err(self.lex_error("Invalid UTF-16 in the code", &[], start));
+        }
+
+        let units = bytes[2..].chunks(2).map(|x| if big_endian {
+            u16::from(x[0]) << 8 | u16::from(x[1])
+        } else {
+            u16::from(x[1]) << 8 | u16::from(x[0])
+        });
+
+        match char::decode_utf16(units).collect::<Result<String, _>>() {
+            Ok(code) => {
+                self.reader = Box::new(io::Cursor::new(code.into_bytes()));
+                Ok(())
+            }
+            Err(_) => Err(self.lex_error("Invalid UTF-16 in the code", &[], start)),
+        }
+    }
+
+    // Whitespace and comments before the next token. Only doc comments are kept unless the
+    // trivia is kept, other comments are tokens then.
+    fn read_trivia(&mut self) -> Result<Vec<Trivia>, LexError> {
+        let mut trivia = vec![];
 
-                i += 1;
-                tokens.push(Token {
-                    token_type: TokenType::String(tmp),
-                    span: None,
-                });
+        loop {
+            // If char is whitespace, just pass the current char
+            let whitespace = self.eat_while(char::is_whitespace);
+            if self.options.keep_trivia && !whitespace.is_empty() {
+                trivia.push(Trivia::Whitespace(whitespace));
             }
+
+            let start = self.position;
+            let from = self.pos;
+            if self.starts_doc_comment() {
+                self.eat_while(|x| x != '\n');
+                trivia.push(Trivia::DocComment(self.buffer[from..self.pos].to_string()));
+            } else if self.options.keep_trivia && self.starts_comment() {
+                self.read_comment(start)?;
+                trivia.push(Trivia::Comment(self.buffer[from..self.pos].to_string()));
+            } else {
+                return Ok(trivia);
+            }
+        }
+    }
+
+    fn read_token_type(&mut self, start: Position) -> Result<TokenType, LexError> {
+        let current_char = match self.peek() {
+            Some(x) => x,
+            None => return Ok(TokenType::EOF), // End od file Token
+        };
+
+        let token_type = match current_char {
+            // If char can start an identifier
+            x if is_identifier_start(x) => self.read_word(),
+            // If current char is a digit, only the ASCII digits start a number
+            x if x.is_ascii_digit() => self.read_number(start)?,
+            // If current char is a starting of a triple quoted string, it is read as it is
+            '"' if self.starts_with("\"\"\"") => self.read_raw_string(start)?,
+            // If current char is a starting of a string
+            '"' => self.read_string(start)?,
             // If current char is a real char
-            else if current_char == '\'' {
-                let tmp = self.code.chars().nth(i + 1).unwrap();
-                i += 2;
-
-                if self.nth_char(i) == '\'' {
-                    tokens.push(Token {
-                        token_type: TokenType::Char(tmp.to_string()),
-                        span: None,
-                    });
-                    i += 1;
+            '\'' => self.read_char(start)?,
+            // If current char is an equals (=) or equal to (==)
+            '=' => {
+                self.bump();
+                if self.eat('=') {
+                    TokenType::EqualEqual
                 } else {
-                    self.unexpected_token(self.nth_char(i), i);
+                    TokenType::Equals
                 }
             }
-            // If current char is an equals (=)
-            else if current_char == '=' {
-                tokens.push(Token {
-                    token_type: TokenType::Equals,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a plus (+)
-            else if current_char == '+' {
-                tokens.push(Token {
-                    token_type: TokenType::Plus,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a minus (-)
-            else if current_char == '-' {
-                tokens.push(Token {
-                    token_type: TokenType::Minus,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a multiple (*)
-            else if current_char == '*' {
-                tokens.push(Token {
-                    token_type: TokenType::Multiple,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a divide (/) or comment ( starts with // )
-            else if current_char == '/' {
-
-                i += 1;
-                if i < char_count && self.nth_char(i) == '/' {
-                    while i < char_count && self.nth_char(i) != '\n' {
-                        i += 1;
-                    }
-
-                    i += 1;
-                    tokens.push(Token {
-                        token_type: TokenType::Comment,
-                        span: None,
-                    });
+            // If current char is a not equal to (!=)
+            '!' => {
+                self.bump();
+                if !self.eat('=') {
+                    return Err(self.unexpected_token(current_char, start));
+                }
+                TokenType::NotEqual
+            }
+            // If current char is a question mark (?) or nil coalescing (??)
+            '?' => {
+                self.bump();
+                if self.eat('?') {
+                    TokenType::Coalesce
                 } else {
-                    tokens.push(Token {
-                        token_type: TokenType::Divide,
-                        span: None,
-                    });
+                    TokenType::Question
                 }
             }
-            // If current char is a mod (%)
-            else if current_char == '%' {
-                tokens.push(Token {
-                    token_type: TokenType::Mod,
-                    span: None,
-                });
-                i += 1;
+            // If current char is a comment ( starts with // or /* )
+            '/' if self.starts_comment() => {
+                self.read_comment(start)?;
+                TokenType::Comment
             }
+            // If current char is a divide (/)
+            '/' => self.single(TokenType::Divide),
             // If current char is a greater than (>) or greater than or equal to (>=)
-            else if current_char == '>' {
-                if i + 1 < char_count && self.code.chars().nth(i + 1).unwrap() == '=' {
-                    tokens.push(Token {
-                        token_type: TokenType::GreaterEqual,
-                        span: None,
-                    });
-                    i += 1;
+            '>' => {
+                self.bump();
+                if self.eat('=') {
+                    TokenType::GreaterEqual
                 } else {
-                    tokens.push(Token {
-                        token_type: TokenType::Greater,
-                        span: None,
-                    });
+                    TokenType::Greater
                 }
-
-                i += 1;
             }
             // If current char is a lesser than (<) or lesser than or equal to (<=)
-            else if current_char == '<' {
-                if i < char_count && self.code.chars().nth(i + 1).unwrap() == '=' {
-                    tokens.push(Token {
-                        token_type: TokenType::LesserEqual,
-                        span: None,
-                    });
-                    i += 1;
+            '<' => {
+                self.bump();
+                if self.eat('=') {
+                    TokenType::LesserEqual
                 } else {
-                    tokens.push(Token {
-                        token_type: TokenType::Lesser,
-                        span: None,
-                    });
+                    TokenType::Lesser
                 }
-                i += 1;
-            }
-            // If current char is an Open Paranthesis ( ( )
-            else if current_char == '(' {
-                tokens.push(Token {
-                    token_type: TokenType::LParen,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a Close Paranthesis ( ) )
-            else if current_char == ')' {
-                tokens.push(Token {
-                    token_type: TokenType::RParen,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is an Open Braces ( { )
-            else if current_char == '{' {
-                tokens.push(Token {
-                    token_type: TokenType::LBrace,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a Close Braces ( } )
-            else if current_char == '}' {
-                tokens.push(Token {
-                    token_type: TokenType::RBrace,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is an Open Brackets ( [ )
-            else if current_char == '[' {
-                tokens.push(Token {
-                    token_type: TokenType::LBracket,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is a Close Brackets ( ] )
-            else if current_char == ']' {
-                tokens.push(Token {
-                    token_type: TokenType::RBracket,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is an semicolon ( ; )
-            else if current_char == ',' {
-                tokens.push(Token {
-                    token_type: TokenType::Comma,
-                    span: None,
-                });
-                i += 1;
-            }
-            // If current char is an semicolon ( ; )
-            else if current_char == ';' {
-                tokens.push(Token {
-                    token_type: TokenType::Semicolon,
-                    span: None,
-                });
-                i += 1;
             }
+            '+' => self.single(TokenType::Plus),
+            '-' => self.single(TokenType::Minus),
+            '*' => self.single(TokenType::Multiple),
+            '%' => self.single(TokenType::Mod),
+            '(' => self.single(TokenType::LParen),
+            ')' => self.single(TokenType::RParen),
+            '{' => self.single(TokenType::LBrace),
+            '}' => self.single(TokenType::RBrace),
+            '[' => self.single(TokenType::LBracket),
+            ']' => self.single(TokenType::RBracket),
+            ',' => self.single(TokenType::Comma),
+            ';' => self.single(TokenType::Semicolon),
             // Else throw an exception
-            else {
-                self.unexpected_token(current_char, i);
+            x => return Err(self.unexpected_token(x, start)),
+        };
+
+        Ok(token_type)
+    }
+
+    // Read the next line of the code into the buffer, returns false at the end of the code.
+    fn fill(&mut self) -> bool {
+        if self.read_error.is_some() {
+            return false;
+        }
+
+        let mut line: Vec<u8> = vec![];
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => false,
+            Ok(_) => {
+                match String::from_utf8(line) {
+                    Ok(x) => {
+                        self.buffer.push_str(&x);
+                        true
+                    }
+                    Err(error) => {
+                        // Error is at the first invalid byte of the line.
+                        let valid = error.utf8_error().valid_up_to();
+                        let line = String::from_utf8_lossy(&error.into_bytes()[..valid])
+                            .into_owned();
+                        let position = line.chars()
+                            .fold(self.end_of_buffer(), advance_position);
+                        let message = "The code is not valid UTF-8, it must be saved as UTF-8";
+                        self.read_error = Some(self.lex_error(message, &[], position));
+                        false
+                    }
+                }
+            }
+            Err(error) => {
+                let position = self.end_of_buffer();
+                let error = error.to_string();
+                self.read_error = Some(self.lex_error("Could not read the code: {}",
+                                                      &[&error],
+                                                      position));
+                false
             }
         }
+    }
 
-        // End od file Token
-        tokens.push(Token {
-            token_type: TokenType::EOF,
-            span: None,
-        });
+    // Position right after the chars in the buffer, where the next line is read.
+    fn end_of_buffer(&self) -> Position {
+        let mut position = self.position;
+        for x in self.buffer[self.pos..].chars() {
+            position = advance_position(position, x);
+        }
 
-        self.tokens = tokens;
+        position
     }
 
-    pub fn current_token(&mut self) -> Token {
-        self.tokens[self.pos].to_owned()
+    fn peek(&mut self) -> Option<char> {
+        if self.pos == self.buffer.len() && !self.fill() {
+            return None;
+        }
+
+        self.buffer[self.pos..].chars().next()
     }
 
-    pub fn next_token(&mut self) -> Token {
-        self.pos += 1;
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if let Some(x) = c {
+            self.pos += x.len_utf8();
+            self.position = advance_position(self.position, x);
+        }
+
+        c
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn starts_with(&mut self, text: &str) -> bool {
+        while self.buffer.len() - self.pos < text.len() && self.fill() {}
+
+        self.buffer[self.pos..].starts_with(text)
+    }
+
+    // Advance while the chars match and return the text that is passed.
+    fn eat_while<F>(&mut self, predicate: F) -> String
+        where F: Fn(char) -> bool
+    {
+        let mut text = String::new();
+        while let Some(x) = self.peek() {
+            if !predicate(x) {
+                break;
+            }
+
+            text.push(x);
+            self.bump();
+        }
+
+        text
+    }
+
+    fn single(&mut self, token_type: TokenType) -> TokenType {
+        self.bump();
+        token_type
+    }
 
+    // Token that starts at 'start' and ends at the current position.
+    fn token(&self, token_type: TokenType, start: Position) -> Token {
+        Token {
+            token_type: token_type,
+            span: Some(Span {
+                lo: start.offset,
+                hi: self.position.offset,
+            }),
+            trivia: vec![],
+            text: None,
+        }
+    }
+
+    // Doc comments start with three slashes, four or more slashes are a plain comment.
+    fn starts_doc_comment(&mut self) -> bool {
+        self.starts_with("///") && !self.starts_with("////")
+    }
+
+    fn starts_comment(&mut self) -> bool {
+        self.starts_with("//") || self.starts_with("/*")
+    }
+
+    // Pass a line comment or a block comment, block comments can be nested.
+    fn read_comment(&mut self, start: Position) -> Result<(), LexError> {
+        if self.starts_with("//") {
+            let comment = self.eat_while(|x| x != '\n');
+            if start.line == self.first_line && start.column == 1 {
+                self.read_pragma(&comment[2..], start)?;
+            }
+            return Ok(());
+        }
+
+        let mut depth = 0;
         loop {
-            if self.tokens[self.pos].token_type == TokenType::Comment {
-                self.pos += 1;
+            if self.starts_with("/*") {
+                depth += 1;
+            } else if self.starts_with("*/") {
+                depth -= 1;
+            } else if self.bump().is_some() {
+                continue;
             } else {
+                return Err(self.lex_error("Unterminated block comment", &[], start));
+            }
+
+            // Both chars of the delimiter are passed, so '/*/' does not end the comment.
+            self.bump();
+            self.bump();
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    // Language pragma on the first line like '// language: turkish' or '// dil: türkçe', it
+    // sets the language of the rest of the code. Pragma is after the shebang line if there is.
+    fn read_pragma(&mut self, comment: &str, start: Position) -> Result<(), LexError> {
+        let colon = match comment.find(':') {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let key = comment[..colon].trim().to_lowercase();
+        if key != "language" && key != "dil" {
+            return Ok(());
+        }
+
+        let name = comment[colon + 1..].trim();
+        match Language::from_name(name) {
+            Some(language) => {
+                self.options.language = language;
+                Ok(())
+            }
+            None => Err(self.lex_error("Unknown language: {}", &[name], start)),
+        }
+    }
+
+    fn read_word(&mut self) -> TokenType {
+        // We have the word, now we need to find what it is and tokenize it.
+        // Words are compared in NFC, so the same letter written with a combining mark is the
+        // same identifier. ASCII words are already in NFC.
+        let mut word = self.eat_while(is_identifier_continue);
+        if !word.is_ascii() {
+            word = word.nfc().collect::<String>();
+        }
+        if self.options.case_insensitive {
+            word = word.to_lowercase();
+        }
+
+        match keyword_token(&word, self.options.language) {
+            Some(token_type) => token_type,
+            // Lowercased names of the built-ins like 'tostring' are written as they are known.
+            None if self.options.case_insensitive => {
+                let name = self.options.language.lowercase_builtin(&word).map(str::to_string);
+                TokenType::Identifier(name.unwrap_or(word))
+            }
+            None => TokenType::Identifier(word),
+        }
+    }
+
+    // Read a number literal like '42', '1_000', '2.5e-3', '0xFF', '0b1010' or '0o17'.
+    // Underscores are separators, they are removed from the literal.
+    fn read_number(&mut self, start: Position) -> Result<TokenType, LexError> {
+        let radix = if self.starts_with("0x") || self.starts_with("0X") {
+            16
+        } else if self.starts_with("0b") || self.starts_with("0B") {
+            2
+        } else if self.starts_with("0o") || self.starts_with("0O") {
+            8
+        } else {
+            10
+        };
+
+        let mut literal = String::new();
+        if radix != 10 {
+            literal.push(self.bump().unwrap());
+            literal.push(self.bump().unwrap().to_ascii_lowercase());
+            if !self.read_digits(radix, &mut literal) {
+                return Err(self.invalid_number(start));
+            }
+        } else {
+            self.read_digits(10, &mut literal);
+
+            // Fraction '2.5', digits are needed after the dot.
+            if self.eat('.') {
+                literal.push('.');
+                if !self.read_digits(10, &mut literal) {
+                    return Err(self.invalid_number(start));
+                }
+            }
+
+            // Exponent '1e3' or '2.5E-3'
+            if self.peek() == Some('e') || self.peek() == Some('E') {
+                self.bump();
+                literal.push('e');
+                if self.peek() == Some('+') || self.peek() == Some('-') {
+                    literal.push(self.bump().unwrap());
+                }
+                if !self.read_digits(10, &mut literal) {
+                    return Err(self.invalid_number(start));
+                }
+            }
+        }
+
+        // Letters, digits or a dot right after the literal like '12abc', '0b12' or '1.5.2' make
+        // it malformed.
+        if self.peek().map_or(false, |x| x == '.' || is_identifier_continue(x)) {
+            return Err(self.invalid_number(start));
+        }
+
+        Ok(TokenType::Number(literal))
+    }
+
+    // Read the digits of the radix and the separators between them into the literal, returns
+    // false if there is no digit.
+    fn read_digits(&mut self, radix: u32, literal: &mut String) -> bool {
+        let mut found = false;
+        while let Some(x) = self.peek() {
+            if x.is_digit(radix) {
+                literal.push(x);
+                found = true;
+            } else if x != '_' {
                 break;
             }
+            self.bump();
+        }
+
+        found
+    }
+
+    fn invalid_number(&mut self, start: Position) -> LexError {
+        // Rest of the literal is passed, so the whole of it is in the message.
+        self.eat_while(|x| x == '.' || is_identifier_continue(x));
+        let length = self.position.offset - start.offset;
+        let literal = format!("{:?}", &self.buffer[self.pos - length..self.pos]);
+        self.lex_error("Invalid number literal: {}", &[&literal], start)
+    }
+
+    fn read_raw_string(&mut self, start: Position) -> Result<TokenType, LexError> {
+        for _ in 0..3 {
+            self.bump();
+        }
+
+        // Skip the line break right after the opening quotes
+        self.eat('\r');
+        self.eat('\n');
+
+        // Closing quotes may be in a line that is not read yet.
+        let mut searched = self.pos;
+        let length = loop {
+            if let Some(x) = self.buffer[searched..].find("\"\"\"") {
+                break searched + x - self.pos;
+            }
+
+            // Quotes at the end of the read code may be the start of the closing quotes.
+            searched = cmp::max(self.pos, self.buffer.len().saturating_sub(2));
+            while !self.buffer.is_char_boundary(searched) {
+                searched += 1;
+            }
+
+            if !self.fill() {
+                return Err(self.lex_error("Unterminated string literal", &[], start));
+            }
+        };
+
+        let text = self.buffer[self.pos..self.pos + length].to_string();
+        for _ in 0..text.chars().count() + 3 {
+            self.bump();
+        }
+
+        Ok(TokenType::String(text))
+    }
+
+    fn read_string(&mut self, start: Position) -> Result<TokenType, LexError> {
+        let mut tmp = "".to_string();
+        let mut parts: Vec<TemplatePart> = vec![];
+        self.bump();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.lex_error("Unterminated string literal", &[], start)),
+                Some('"') => break,
+                Some('\\') => tmp.push(self.read_escape()?),
+                Some('$') if self.starts_with("${") => {
+                    // Embedded expression, text before it becomes a literal part
+                    if !tmp.is_empty() {
+                        parts.push(TemplatePart::Literal(tmp));
+                        tmp = "".to_string();
+                    }
+
+                    let code = self.read_interpolation()?;
+                    parts.push(TemplatePart::Code(code));
+                }
+                Some(x) => {
+                    tmp.push(x);
+                    self.bump();
+                }
+            }
+        }
+
+        self.bump();
+        if parts.is_empty() {
+            return Ok(TokenType::String(tmp));
         }
 
-        self.tokens[self.pos].to_owned()
+        if !tmp.is_empty() {
+            parts.push(TemplatePart::Literal(tmp));
+        }
+
+        Ok(TokenType::Template(parts))
     }
 
-    fn is_keyword(&self, value: &str) -> bool {
-        value == "main" || value == "number" || value == "string" || value == "bool" ||
-        value == "return"
+    fn read_char(&mut self, start: Position) -> Result<TokenType, LexError> {
+        self.bump();
+
+        let tmp = match self.peek() {
+            None => return Err(self.lex_error("Unterminated char literal", &[], start)),
+            Some('\'') => return Err(self.lex_error("Empty char literal", &[], start)),
+            Some('\\') => self.read_escape()?,
+            Some(x) => {
+                self.bump();
+                x
+            }
+        };
+
+        if !self.eat('\'') {
+            return Err(self.lex_error("Unterminated char literal", &[], start));
+        }
+
+        Ok(TokenType::Char(tmp.to_string()))
     }
 
-    fn unexpected_token(&self, c: char, i: usize) {
-        let mut line_count = 1;
-        let mut column: usize = 0;
-        let mut is_first_line = true;
+    // Read an escape sequence starting with a backslash and advance past it.
+    fn read_escape(&mut self) -> Result<char, LexError> {
+        let start = self.position;
+        self.bump();
+
+        let escaped = match self.bump() {
+            Some(x) => x,
+            None => return Err(self.lex_error("Unterminated escape sequence", &[], start)),
+        };
+
+        Ok(match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '$' => '$',
+            'u' => {
+                // Unicode escape like '\u{1F600}'
+                if !self.eat('{') {
+                    return Err(self.lex_error("Expected '{' in unicode escape", &[], start));
+                }
+
+                let hex = self.eat_while(|x| x != '}');
+                if !self.eat('}') {
+                    return Err(self.lex_error("Unterminated unicode escape", &[], start));
+                }
 
-        for curr_index in (0..i).rev() {
-            if self.code.chars().nth(curr_index).unwrap() == '\n' {
-                if is_first_line {
-                    column = i - curr_index;
-                    is_first_line = false;
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(x) if hex.len() <= 6 => x,
+                    _ => {
+                        let message = "Invalid unicode escape '\\u{{}}'";
+                        return Err(self.lex_error(message, &[&hex], start));
+                    }
                 }
-                line_count += 1;
             }
+            x => {
+                let escape = x.to_string();
+                return Err(self.lex_error("Unknown escape sequence '\\{}'", &[&escape], start));
+            }
+        })
+    }
+
+    // Read source code of an embedded expression starting with "${" and advance past its
+    // closing brace. Braces in the strings and chars of the code are not counted.
+    fn read_interpolation(&mut self) -> Result<String, LexError> {
+        let start = self.position;
+        let mut code = "".to_string();
+        let mut depth = 0;
+        let mut quote = None; // Quote of the string or char that the code is in
+        self.bump();
+        self.bump();
+
+        loop {
+            match (self.peek(), quote) {
+                (None, _) => {
+                    return Err(self.lex_error("Unterminated string interpolation", &[], start))
+                }
+                (Some('\\'), Some(_)) => {
+                    // Escaped char can't end the string
+                    code.push(self.bump().unwrap());
+                    if self.peek().is_none() {
+                        continue;
+                    }
+                }
+                (Some(x), Some(y)) if x == y => quote = None,
+                (Some(_), Some(_)) => {}
+                (Some(x), None) if x == '"' || x == '\'' => quote = Some(x),
+                (Some('}'), None) if depth == 0 => break,
+                (Some('{'), None) => depth += 1,
+                (Some('}'), None) => depth -= 1,
+                _ => {}
+            }
+
+            code.push(self.bump().unwrap());
+        }
+
+        self.bump();
+        if code.trim().is_empty() {
+            return Err(self.lex_error("Empty string interpolation", &[], start));
         }
 
-        panic!("Unexpected token: {:?} at line {:?}, column {:?}!",
-               c,
-               line_count,
-               column);
+        Ok(code)
     }
 
-    fn nth_char(&self, index: usize) -> char {
-        self.code.chars().nth(index).unwrap()
+    fn unexpected_token(&self, c: char, start: Position) -> LexError {
+        self.lex_error("Unexpected token: {}", &[&format!("{:?}", c)], start)
+    }
+
+    // Error with the message in the language of the code, '{}' in the message are replaced with
+    // the arguments.
+    fn lex_error(&self, message: &str, args: &[&str], start: Position) -> LexError {
+        LexError {
+            message: self.options.language.message(message, args),
+            line: start.line,
+            column: start.column,
+            language: self.options.language,
+        }
+    }
+}
+
+fn advance_position(position: Position, c: char) -> Position {
+    if c == '\n' {
+        Position {
+            offset: position.offset + 1,
+            line: position.line + 1,
+            column: 1,
+        }
+    } else {
+        Position {
+            offset: position.offset + c.len_utf8(),
+            line: position.line,
+            column: position.column + 1,
+        }
+    }
+}
+
+// Token of the keyword in the given language.
+fn keyword_token(word: &str, language: Language) -> Option<TokenType> {
+    KEYWORDS.iter()
+        .find(|&&(_, english, turkish)| language.choose(english, turkish) == word)
+        .map(|&(ref token_type, _, _)| token_type.clone())
+}
+
+// Word of a keyword token in the given language like "while" for 'TokenType::While'.
+pub fn keyword(token_type: &TokenType, language: Language) -> Option<&'static str> {
+    KEYWORDS.iter()
+        .find(|&&(ref x, _, _)| x == token_type)
+        .map(|&(_, english, turkish)| language.choose(english, turkish))
+}
+
+// Value of a number literal that is read by the lexer, like 255 for '0xff'.
+pub fn number_value(literal: &str) -> Option<f64> {
+    let radix = match literal.get(..2) {
+        Some("0x") => 16,
+        Some("0b") => 2,
+        Some("0o") => 8,
+        _ => return literal.parse::<f64>().ok(),
+    };
+
+    let mut value = 0.0;
+    for digit in literal[2..].chars() {
+        value = value * f64::from(radix) + f64::from(digit.to_digit(radix)?);
+    }
+
+    Some(value)
+}
+
+// Identifiers follow the Unicode XID rules, underscore is allowed anywhere in them.
+fn is_identifier_start(c: char) -> bool {
+    c == '_' || UnicodeXID::is_xid_start(c)
+}
+
+fn is_identifier_continue(c: char) -> bool {
+    c == '_' || UnicodeXID::is_xid_continue(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufReader, Read};
+
+    use super::*;
+
+    fn lex_bytes(code: &[u8], options: LexerOptions) -> Result<Vec<Token>, LexError> {
+        Lexer::with_options(io::Cursor::new(code.to_vec()), options).collect()
+    }
+
+    fn lex_with(code: &str, options: LexerOptions) -> Result<Vec<Token>, LexError> {
+        lex_bytes(code.as_bytes(), options)
+    }
+
+    fn lex(code: &str) -> Result<Vec<Token>, LexError> {
+        lex_with(code, LexerOptions::default())
+    }
+
+    fn token_types(code: &str) -> Vec<TokenType> {
+        lex(code).unwrap().into_iter().map(|x| x.token_type).collect()
+    }
+
+    // Message, line and column of the error of the code.
+    fn lex_error(code: &str) -> (String, usize, usize) {
+        lex_bytes_error(code.as_bytes())
+    }
+
+    fn lex_bytes_error(code: &[u8]) -> (String, usize, usize) {
+        match lex_bytes(code, LexerOptions::default()) {
+            Ok(tokens) => panic!("Expected an error, found {:?}", tokens),
+            Err(error) => (error.message, error.line, error.column),
+        }
+    }
+
+    fn number(literal: &str) -> TokenType {
+        TokenType::Number(literal.to_string())
+    }
+
+    fn identifier(name: &str) -> TokenType {
+        TokenType::Identifier(name.to_string())
+    }
+
+    fn encode_utf16(code: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian { vec![0xFE, 0xFF] } else { vec![0xFF, 0xFE] };
+        for unit in code.encode_utf16() {
+            let (high, low) = ((unit >> 8) as u8, unit as u8);
+            bytes.extend(if big_endian { [high, low] } else { [low, high] }.iter());
+        }
+
+        bytes
+    }
+
+    // Reader that fails like a closed pipe.
+    struct BrokenReader;
+
+    impl Read for BrokenReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+        }
+    }
+
+    #[test]
+    fn statement() {
+        assert_eq!(token_types("number x = 1 + y;"),
+                   vec![TokenType::Type(Type::Number),
+                        identifier("x"),
+                        TokenType::Equals,
+                        TokenType::Number("1".to_string()),
+                        TokenType::Plus,
+                        identifier("y"),
+                        TokenType::Semicolon,
+                        TokenType::EOF]);
+    }
+
+    #[test]
+    fn keywords() {
+        assert_eq!(token_types("if else while iff fn bool"),
+                   vec![TokenType::If,
+                        TokenType::Else,
+                        TokenType::While,
+                        identifier("iff"),
+                        TokenType::Fn,
+                        TokenType::Type(Type::Bool),
+                        TokenType::EOF]);
+        assert_eq!(keyword(&TokenType::While, Language::English), Some("while"));
+        assert_eq!(keyword(&TokenType::While, Language::Turkish), Some("iken"));
+        assert_eq!(keyword(&TokenType::Type(Type::Array), Language::English), Some("array"));
+        assert_eq!(keyword(&TokenType::Plus, Language::English), None);
+        assert_eq!(TokenType::Type(Type::Bool).kind(), TokenKind::Type);
+        assert_eq!(identifier("x").kind(), TokenKind::Identifier);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(token_types("== != >= <= ?? ? > <"),
+                   vec![TokenType::EqualEqual,
+                        TokenType::NotEqual,
+                        TokenType::GreaterEqual,
+                        TokenType::LesserEqual,
+                        TokenType::Coalesce,
+                        TokenType::Question,
+                        TokenType::Greater,
+                        TokenType::Lesser,
+                        TokenType::EOF]);
+    }
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(token_types("\"a ${ \"}\" } b\""),
+                   vec![TokenType::Template(vec![TemplatePart::Literal("a ".to_string()),
+                                                 TemplatePart::Code(" \"}\" ".to_string()),
+                                                 TemplatePart::Literal(" b".to_string())]),
+                        TokenType::EOF]);
+        assert_eq!(lex_error("x = \"${ y \";"),
+                   ("Unterminated string interpolation".to_string(), 1, 6));
+    }
+
+    #[test]
+    fn error_column_counts_chars() {
+        assert_eq!(lex_error("string s = \"şğü\";\nx = ş @"),
+                   ("Unexpected token: '@'".to_string(), 2, 7));
+    }
+
+    #[test]
+    fn streaming() {
+        // Tokens before a read error are produced, the code after them isn't needed yet.
+        let code = io::Cursor::new(b"number x = 1;\n".to_vec());
+        let reader = BufReader::new(code.chain(BrokenReader));
+        let results: Vec<Result<Token, LexError>> = Lexer::from_reader(reader).collect();
+        assert_eq!(results.len(), 6);
+        assert_eq!(results[4].as_ref().unwrap().token_type, TokenType::Semicolon);
+
+        let error = results[5].as_ref().unwrap_err();
+        assert_eq!((&*error.message, error.line, error.column),
+                   ("Could not read the code: broken pipe", 2, 1));
+
+        let mut stream = TokenStream::new("x // comment\n+ y".to_string());
+        assert_eq!(stream.peek(1).token_type, TokenType::Plus);
+        assert_eq!(stream.next_token().token_type, identifier("x"));
+        assert_eq!(stream.next_token().token_type, TokenType::Plus);
+        assert_eq!(stream.next_token().token_type, identifier("y"));
+        assert_eq!(stream.next_token().token_type, TokenType::EOF);
+        assert_eq!(stream.next_token().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn case_sensitive_words() {
+        assert_eq!(token_types("Count TRUE true"),
+                   vec![identifier("Count"), identifier("TRUE"), TokenType::True, TokenType::EOF]);
+
+        let options = LexerOptions { case_insensitive: true, ..LexerOptions::default() };
+        let tokens = lex_with("Count TRUE ToString pi YAZ", options).unwrap();
+        let token_types: Vec<TokenType> = tokens.into_iter().map(|x| x.token_type).collect();
+        assert_eq!(token_types,
+                   vec![identifier("count"),
+                        TokenType::True,
+                        identifier("toString"),
+                        identifier("PI"),
+                        identifier("print"),
+                        TokenType::EOF]);
+    }
+
+    #[test]
+    fn separator_starts_identifier() {
+        assert_eq!(token_types("_1 x_1 user_name"),
+                   vec![identifier("_1"),
+                        identifier("x_1"),
+                        identifier("user_name"),
+                        TokenType::EOF]);
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        // 'ı' is a letter, 'e' with a combining acute accent is the same name as 'é'.
+        assert_eq!(token_types("sayı e\u{301}"),
+                   vec![identifier("sayı"), identifier("é"), TokenType::EOF]);
+        assert_eq!(lex_error("x = €;"), ("Unexpected token: '€'".to_string(), 1, 5));
+    }
+    #[test]
+    fn numbers() {
+        assert_eq!(token_types("42 2.5 1e-3 2.5E+3 1_000_000 0xFF 0b1010 0o17 0x_ff"),
+                   vec![number("42"),
+                        number("2.5"),
+                        number("1e-3"),
+                        number("2.5e+3"),
+                        number("1000000"),
+                        number("0xFF"),
+                        number("0b1010"),
+                        number("0o17"),
+                        number("0xff"),
+                        TokenType::EOF]);
+
+        assert_eq!(number_value("0xff"), Some(255.0));
+        assert_eq!(number_value("0b1010"), Some(10.0));
+        assert_eq!(number_value("0o17"), Some(15.0));
+        assert_eq!(number_value("2.5e+3"), Some(2500.0));
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        for &(code, literal) in &[("0x", "\"0x\""),
+                                  ("0b12", "\"0b12\""),
+                                  ("1.5.2", "\"1.5.2\""),
+                                  ("1e", "\"1e\""),
+                                  ("1e+", "\"1e+\""),
+                                  ("1.", "\"1.\""),
+                                  ("12abc", "\"12abc\""),
+                                  ("0o8", "\"0o8\""),
+                                  ("1\u{663}", "\"1\u{663}\"")] {
+            let message = format!("Invalid number literal: {}", literal);
+            assert_eq!(lex_error(code), (message, 1, 1), "{}", code);
+        }
+
+        // Digits of other scripts are not numbers.
+        assert_eq!(lex_error("\u{663}"), ("Unexpected token: '\u{663}'".to_string(), 1, 1));
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        assert_eq!(token_types("a /* b /* c */ d */ e // f"),
+                   vec![identifier("a"),
+                        TokenType::Comment,
+                        identifier("e"),
+                        TokenType::Comment,
+                        TokenType::EOF]);
+        assert_eq!(lex_error("a /* b /* c */"),
+                   ("Unterminated block comment".to_string(), 1, 3));
+    }
+
+    #[test]
+    fn doc_comment() {
+        let tokens = lex("/// Count of items\n/// in the list\nnumber count;").unwrap();
+        assert_eq!(tokens[0].doc(), Some("Count of items\nin the list".to_string()));
+        assert_eq!(tokens[1].doc(), None);
+    }
+
+    #[test]
+    fn trivia_round_trip() {
+        let options = LexerOptions { keep_trivia: true, ..LexerOptions::default() };
+        let codes = ["number x = 0x_FF; // hex\n/* a /* nested */ block */\n",
+                     "\u{feff}#!/usr/bin/env interpreter\n/// doc\nfn f() { return; }\n",
+                     "yaz(\"a ${ \"}\" } b\", 'c');\t\r\n  ",
+                     ""];
+
+        for code in &codes {
+            let mut text = String::new();
+            for token in lex_with(code, options).unwrap() {
+                for trivia in &token.trivia {
+                    match *trivia {
+                        Trivia::Whitespace(ref x) |
+                        Trivia::Comment(ref x) |
+                        Trivia::DocComment(ref x) => text.push_str(x),
+                    }
+                }
+                text.push_str(token.text.as_ref().unwrap());
+            }
+
+            assert_eq!(&text, code);
+        }
+    }
+
+    #[test]
+    fn language_pragma() {
+        assert_eq!(token_types("// dil: türkçe\neğer (doğru) { döndür boş; } if"),
+                   vec![TokenType::Comment,
+                        TokenType::If,
+                        TokenType::LParen,
+                        TokenType::True,
+                        TokenType::RParen,
+                        TokenType::LBrace,
+                        TokenType::Return,
+                        TokenType::Nil,
+                        TokenType::Semicolon,
+                        TokenType::RBrace,
+                        identifier("if"),
+                        TokenType::EOF]);
+
+        // Pragma is only read on the first line, the message of an error is in the language.
+        assert_eq!(token_types("\n// language: turkish\neğer"),
+                   vec![TokenType::Comment, identifier("eğer"), TokenType::EOF]);
+        assert_eq!(lex_error("// language: klingon"),
+                   ("Unknown language: klingon".to_string(), 1, 1));
+        assert_eq!(lex_error("// language: turkish\nx = @;"),
+                   ("Beklenmeyen karakter: '@'".to_string(), 2, 5));
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        assert_eq!(token_types("\u{feff}x"), vec![identifier("x"), TokenType::EOF]);
+
+        // Columns don't count the byte order mark.
+        assert_eq!(lex_error("\u{feff}x @"), ("Unexpected token: '@'".to_string(), 1, 3));
+    }
+
+    #[test]
+    fn shebang() {
+        assert_eq!(token_types("#!/usr/bin/env interpreter\nx"),
+                   vec![identifier("x"), TokenType::EOF]);
+        assert_eq!(lex_error("x #!"), ("Unexpected token: '#'".to_string(), 1, 3));
+
+        // Language pragma can be on the line after the shebang.
+        assert_eq!(token_types("#!/usr/bin/env interpreter\n// dil: türkçe\neğer"),
+                   vec![TokenType::Comment, TokenType::If, TokenType::EOF]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let message = "The code is not valid UTF-8, it must be saved as UTF-8".to_string();
+        assert_eq!(lex_bytes_error(b"x = 1;\nyaz(\"ab\xffcd\");"), (message, 2, 8));
+    }
+
+    #[test]
+    fn utf16_with_byte_order_mark() {
+        let code = "number x = \"é\";";
+        let expected = token_types(code);
+
+        for &big_endian in &[false, true] {
+            let bytes = encode_utf16(code, big_endian);
+            let tokens = lex_bytes(&bytes, LexerOptions::default()).unwrap();
+            let token_types: Vec<TokenType> = tokens.into_iter().map(|x| x.token_type).collect();
+            assert_eq!(token_types, expected);
+        }
+    }
+
+    #[test]
+    fn invalid_utf16() {
+        let message = "Invalid UTF-16 in the code".to_string();
+
+        // Odd number of bytes
+        assert_eq!(lex_bytes_error(b"\xff\xfex\x00y"), (message.clone(), 1, 1));
+
+        // Unpaired surrogate
+        assert_eq!(lex_bytes_error(b"\xff\xfex\x00\x00\xd8y\x00"), (message, 1, 1));
     }
 }
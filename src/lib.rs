@@ -0,0 +1,26 @@
+#![feature(plugin)]
+
+#![plugin(clippy)]
+
+//! Interpreter for Basic C like language.
+//!
+//! `Engine` is the entry point for embedding: it evaluates source code and keeps the
+//! global variables between evaluations. Lower level modules are public for tools that
+//! need the tokens or the syntax tree.
+
+extern crate unicode_normalization;
+extern crate unicode_xid;
+
+pub mod lexer;
+pub mod parser;
+pub mod ast;
+pub mod interpreter;
+pub mod checker;
+pub mod language;
+mod builtins;
+mod engine;
+
+pub use engine::{Engine, Error};
+pub use interpreter::Value;
+pub use ast::Type;
+pub use language::Language;
@@ -2,58 +2,252 @@
 
 #![plugin(clippy)]
 
-use std::error::Error;
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
 use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+use std::thread;
+
+extern crate interpreter;
+
+use interpreter::{Engine, Error, Language};
+use interpreter::lexer::{Lexer, LexerOptions, TokenType};
+
+mod repl;
+
+use repl::Repl;
+
+// Exit codes of the command line interface
+const EXIT_RUNTIME: i32 = 1;
+const EXIT_LEX: i32 = 2;
+const EXIT_PARSE: i32 = 3;
+const EXIT_TYPE: i32 = 4;
+const EXIT_USAGE: i32 = 64;
+const EXIT_NO_INPUT: i32 = 66;
+const EXIT_PANIC: i32 = 101;
+
+// Stack of the thread that runs the code and the call depth limit of the interpreter, nested
+// function calls need more stack than the main thread has.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+const MAX_CALL_DEPTH: usize = 2000;
+
+const USAGE: &'static str = "\
+Usage: interpreter [command] [options] <file | -> [script arguments...]
+       interpreter [command] [options] -e <code> [script arguments...]
+       interpreter [repl] [options]
+
+Commands:
+    run       Run the program (default)
+    check     Parse and type-check the program without running it
+    tokens    Print the tokens of the program
+    ast       Print the syntax tree of the program
+    repl      Start the interactive mode (default without a source)
+
+Options:
+    -e <code>   Use the given code instead of a file
+    --strict    Make math domain violations like 'sqrt(-1)' runtime errors
+    --case-insensitive
+                Lowercase all words like the older versions, 'Count' is 'count'
+    --language <en | tr>
+                Language of the keywords, built-in names and messages, a pragma
+                like '// language: turkish' on the first line of the code overrides it
+    -h, --help  Print this message
+
+Source is read from stdin if the file is '-'. Arguments after the source are given to the
+script, it reads them with 'args()'. Program ended with 'exit(code)' exits with that code.";
 
-mod lexer;
-mod parser;
-mod ast;
-mod interpreter;
+enum Command {
+    Run,
+    Check,
+    Tokens,
+    Ast,
+    Repl,
+}
+
+enum Source {
+    File(String), // Path of the source file, '-' is stdin
+    Inline(String), // Code given with '-e'
+}
 
-use lexer::*;
-use parser::*;
-use interpreter::*;
+struct Options {
+    command: Command,
+    strict: bool,
+    lexer_options: LexerOptions,
+    source: Option<Source>, // REPL does not have a source
+    args: Vec<String>,
+}
 
 fn main() {
-    // Try to get file from arguments.
-    // If given run this file, otherwise run "src/test/main.c"
-    let args: Vec<_> = env::args().collect();
-    let mut path = Path::new("src/test/main.c");
-    let mut code = String::new();
-    let display = path.display();
-
-    // For custom source file for interpreting.
-    if args.len() > 1 {
-        println!("Your source file is: {}", args[1]);
-        path = Path::new(&*args[1]);
-    } else {
-        println!("You are using default test source path: src/test/main.c");
-    }
-
-    // Open the source file.
-    let mut file = match File::open(&path) {
-        // The `description` method of `io::Error` returns a string that
-        // describes the error
-        Err(why) => panic!("couldn't open {}: {}", display, Error::description(&why)),
-        Ok(file) => file,
+    let runner = thread::Builder::new().stack_size(STACK_SIZE).spawn(run);
+
+    match runner.map(|x| x.join()) {
+        Ok(Ok(())) => {}
+        Ok(Err(_)) => process::exit(EXIT_PANIC),
+        Err(why) => {
+            eprintln!("Could not start the interpreter: {}", why);
+            process::exit(EXIT_PANIC);
+        }
+    }
+}
+
+fn run() {
+    let options = parse_args(env::args().skip(1).collect());
+
+    let source = match options.source {
+        Some(ref source) => source,
+        None => process::exit(Repl::new(options.strict, options.lexer_options).run()),
+    };
+
+    let reader = match open_source(source) {
+        Ok(reader) => reader,
+        Err(why) => {
+            eprintln!("{}", why);
+            process::exit(EXIT_NO_INPUT);
+        }
+    };
+
+    let mut engine = Engine::new();
+    engine.set_lexer_options(options.lexer_options);
+    engine.interpreter().set_strict(options.strict);
+    engine.interpreter().set_max_call_depth(MAX_CALL_DEPTH);
+    engine.interpreter().set_args(options.args);
+
+    // Code is tokenized while it is read, tokens are printed as soon as they are found.
+    let result = match options.command {
+        Command::Run => engine.parse_reader(reader).and_then(|ast| engine.run(&ast)).map(|_| ()),
+        Command::Check => engine.parse_reader(reader).and_then(|ast| engine.check_ast(&ast)),
+        Command::Tokens => print_tokens(Lexer::with_options(reader, options.lexer_options)),
+        Command::Ast => engine.parse_reader(reader).map(|ast| println!("{:#?}", ast)),
+        Command::Repl => unreachable!(),
+    };
+
+    if let Err(why) = result {
+        eprintln!("{}", why);
+        process::exit(match why {
+            Error::Lex(_) => EXIT_LEX,
+            Error::Parse(_) => EXIT_PARSE,
+            Error::Type(_) => EXIT_TYPE,
+            Error::Runtime(_) => EXIT_RUNTIME,
+        });
+    }
+
+    if let Some(code) = engine.exit_code() {
+        process::exit(code);
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(EXIT_USAGE);
+}
+
+// Read the command, options and the source, everything after the source belongs to the
+// script.
+fn parse_args(args: Vec<String>) -> Options {
+    let mut command = None;
+    let mut strict = false;
+    let mut lexer_options = LexerOptions::default();
+    let mut source = None;
+    let mut script_args: Vec<String> = vec![];
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if source.is_some() {
+            script_args.push(arg);
+            continue;
+        }
+
+        match &*arg {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--strict" => strict = true,
+            "--case-insensitive" => lexer_options.case_insensitive = true,
+            "--language" => {
+                let name = match args.next() {
+                    Some(name) => name,
+                    None => usage_error("Option '--language' requires a language."),
+                };
+                match Language::from_name(&name) {
+                    Some(language) => lexer_options.language = language,
+                    None => usage_error(&format!("Unknown language '{}'.", name)),
+                }
+            }
+            "-e" => {
+                match args.next() {
+                    Some(code) => source = Some(Source::Inline(code)),
+                    None => usage_error("Option '-e' requires code."),
+                }
+            }
+            "run" if command.is_none() => command = Some(Command::Run),
+            "check" if command.is_none() => command = Some(Command::Check),
+            "tokens" if command.is_none() => command = Some(Command::Tokens),
+            "ast" if command.is_none() => command = Some(Command::Ast),
+            "repl" if command.is_none() => command = Some(Command::Repl),
+            x if x.starts_with('-') && x != "-" => {
+                usage_error(&format!("Unknown option '{}'.", x))
+            }
+            _ => source = Some(Source::File(arg)),
+        }
+    }
+
+    let command = match command {
+        Some(command) => command,
+        None if source.is_none() => Command::Repl,
+        None => Command::Run,
     };
 
-    // Read the source file and if it is success pass it to code variable. Otherwise, give error.
-    match file.read_to_string(&mut code) {
-        Err(why) => panic!("couldn't read {}: {}", display, Error::description(&why)),
-        Ok(_) => println!(""),
+    match (&command, &source) {
+        (&Command::Repl, &Some(_)) => usage_error("REPL does not take a source."),
+        (&Command::Repl, &None) => {}
+        (_, &None) => usage_error("No source file given."),
+        (&Command::Run, _) => {}
+        _ if !script_args.is_empty() => {
+            usage_error("Script arguments can only be given to 'run'.")
+        }
+        _ => {}
+    }
+
+    Options {
+        command: command,
+        strict: strict,
+        lexer_options: lexer_options,
+        source: source,
+        args: script_args,
+    }
+}
+
+fn open_source(source: &Source) -> Result<Box<dyn BufRead>, String> {
+    match *source {
+        Source::Inline(ref x) => Ok(Box::new(io::Cursor::new(x.clone().into_bytes()))),
+        Source::File(ref path) if path == "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        Source::File(ref path) => {
+            match File::open(path) {
+                Ok(file) => Ok(Box::new(BufReader::new(file))),
+                Err(why) => Err(format!("Couldn't read {}: {}", path, why)),
+            }
+        }
     }
+}
 
-    // Get Tokens from the Lexer Module
-    let token_stream = TokenStream::new(code);
+fn print_tokens(lexer: Lexer) -> Result<(), Error> {
+    for token in lexer {
+        println!("{}", describe_token(&token?.token_type));
+    }
 
-    // Creating a new Parser instance for AST.
-    let parser = Parser::new(token_stream, None);
+    Ok(())
+}
 
-    // Creating an interpreter module for program.
-    let mut program = Interpreter::new(parser);
-    program.run();
+// Name of the token with its value, like 'Identifier "x"'. It is also used by the REPL.
+fn describe_token(token_type: &TokenType) -> String {
+    match *token_type {
+        TokenType::Type(ref x) => format!("{:?} {:?}", token_type, x.to_string()),
+        TokenType::Identifier(ref x) |
+        TokenType::Char(ref x) |
+        TokenType::String(ref x) |
+        TokenType::Number(ref x) => format!("{:?} {:?}", token_type, x),
+        TokenType::Template(ref parts) => format!("{:?} {:?}", token_type, parts),
+        ref x => format!("{:?}", x),
+    }
 }
@@ -2,42 +2,54 @@
 // Parser Module
 //
 
+use std::fmt;
+use std::io;
 use std::string::String;
-use std::collections::HashMap;
-
 use lexer::*;
 use ast::*;
+use language::Language;
 
-#[derive(Clone)]
 pub struct Parser {
     pub token_stream: TokenStream, // TokenStream
     pub token: Token, // Current token
     pub span: Option<Span>, // Span of current token
-    pub token_count: usize, // Total token count of TokenStream
-    pub current_index: usize, // Current token index of TokenStream
+}
+
+// Error found while parsing the token stream
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub cause: Option<LexError>, // Error of the lexer that ended the tokens early
+    pub language: Language, // Language of the message
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.cause {
+            Some(ref cause) => write!(f, "{}", cause),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
 // Reverse Polish Notation value enum for
-// Shunting-Yard Algorithm to caculate arithmetic values
+// Shunting-Yard Algorithm to build arithmetic expressions
 //
 #[derive(Debug, Clone)]
 enum RPNValue {
     Operator(TokenType),
-    Number(f64),
+    Operand(Box<Expr>),
 }
 
 impl Parser {
     pub fn new(mut _token_stream: TokenStream, _span: Option<Span>) -> Parser {
-        let token_count = _token_stream.tokens.len();
-        let current_token = _token_stream.current_token();
+        let current_token = _token_stream.next_token();
 
         // Create new parser for parsing process
         Parser {
             token_stream: _token_stream,
             token: current_token,
             span: None,
-            token_count: token_count,
-            current_index: 0,
         }
     }
 
@@ -46,104 +58,191 @@ impl Parser {
         format!("{:?}", self.token.token_type)
     }
 
-    fn token_to_string(&self, token_type: &TokenType) -> String {
-        format!("{:?}", token_type)
+    fn unexpected_token(&mut self, ut: &str) -> ParseError {
+        let found = self.peek_token().token_type.clone();
+        self.unexpected(ut, &found)
     }
 
-    fn unexpected_token(&self, ut: &str) {
-        // TODO: Make more user friendly errors. It is temporary for now.
-        panic!("Unexpected token found. Expected: {:?}, Found: {:?} instead.",
-               ut,
-               self.token_stream.tokens[self.current_index + 1].token_type);
+    // Error for a found token that is not the expected one.
+    fn unexpected(&self, ut: &str, found: &TokenType) -> ParseError {
+        let expected = format!("{:?}", ut);
+        let found = format!("{:?}", found);
+        self.parse_error("Unexpected token found. Expected: {}, Found: {} instead.",
+                         &[&expected, &found])
     }
 
-    fn eat_token(&mut self, expected_token: &str) -> bool {
-        let is_exist = self.check_token(expected_token);
+    // Tokens after an error of the lexer are EOF, so the error of the lexer is the cause of
+    // the parse error. Message is in the language of the code, '{}' in it are replaced with
+    // the arguments.
+    fn parse_error(&self, message: &str, args: &[&str]) -> ParseError {
+        let language = self.language();
+        ParseError {
+            message: language.message(message, args),
+            cause: self.token_stream.error().cloned(),
+            language: language,
+        }
+    }
 
-        // If there is a token next, advance token and return true, otherwise return false.
-        if is_exist {
-            self.advance_token()
-        } else {
-            false
+    // Language of the code, it is known after the first token.
+    fn language(&self) -> Language {
+        self.token_stream.options().language
+    }
+
+    // Tokens may be ended early by an error of the lexer.
+    fn check_lex_error(&self) -> Result<(), ParseError> {
+        match self.token_stream.error() {
+            Some(error) => Err(self.parse_error(&error.message, &[])),
+            None => Ok(()),
         }
     }
 
-    fn check_token(&self, expected_token: &str) -> bool {
-        self.token_to_string(&self.token_stream.tokens[self.current_index + 1].token_type) ==
-        expected_token
+    // Next token of the stream, EOF is returned at the end of the stream.
+    fn peek_token(&mut self) -> &Token {
+        self.token_stream.peek(0)
+    }
+
+    // Kind of the next token.
+    fn peek_kind(&mut self) -> TokenKind {
+        self.peek_token().token_type.kind()
+    }
+
+    fn eat_token(&mut self, kind: TokenKind) -> bool {
+        let is_exist = self.check_token(kind);
+
+        // If there is the expected token next, advance token.
+        if is_exist {
+            self.advance_token();
+        }
+
+        is_exist
     }
 
-    fn advance_token(&mut self) -> bool {
-        self.current_index += 1;
+    fn check_token(&mut self, kind: TokenKind) -> bool {
+        self.peek_kind() == kind
+    }
 
-        // If have next token, get next token and return true otherwise return false.
-        if self.current_index != self.token_count {
-            self.token = self.token_stream.next_token();
+    // Eat the next token if it is the expected one, otherwise it is an error.
+    fn expect_token(&mut self, kind: TokenKind) -> Result<(), ParseError> {
+        if self.eat_token(kind) {
+            Ok(())
+        } else {
+            Err(self.unexpected_token(&format!("{:?}", kind)))
         }
+    }
 
-        self.current_index != self.token_count
+    // Eat the next token if it is an identifier and return its name.
+    fn expect_identifier(&mut self) -> Result<String, ParseError> {
+        self.expect_token(TokenKind::Identifier)?;
+        self.get_current_identifier()
+    }
+
+    fn advance_token(&mut self) {
+        self.token = self.token_stream.next_token();
     }
 
     fn eat_operator(&mut self) -> bool {
-        self.eat_token("Plus") || self.eat_token("Minus") || self.eat_token("Multiple") ||
-        self.eat_token("Divide") || self.eat_token("Mod")
+        if precedence(self.peek_kind()).is_some() {
+            self.advance_token();
+            true
+        } else {
+            false
+        }
     }
 
-    fn get_current_number(&mut self) -> f64 {
+    fn get_current_number(&mut self) -> Result<f64, ParseError> {
         match self.token.token_type.clone() {
-            TokenType::Number(ref x) => x.parse::<f64>().unwrap(),
-            _ => panic!("Error while parsing to integer."),
+            TokenType::Number(ref x) => {
+                let message = "Error while parsing to number.";
+                number_value(x).ok_or_else(|| self.parse_error(message, &[]))
+            }
+            _ => Err(self.parse_error("Error while parsing to number.", &[])),
         }
     }
 
-    pub fn parse(&mut self) -> Box<Expr> {
-        let mut block: Vec<Box<Expr>> = vec![];
+    // Read the type of current keyword token, a following question mark makes it optional.
+    fn get_current_type(&mut self) -> Result<Type, ParseError> {
+        let var_type = match self.token.token_type {
+            TokenType::Type(ref x) => x.clone(),
+            TokenType::Fn => Type::Function,
+            _ => return Err(self.parse_error("Error while reading type.", &[])),
+        };
 
-        // Read all tokens and create statements, then push it to the block.
-        while self.current_index < self.token_count {
+        if self.eat_token(TokenKind::Question) {
+            Ok(Type::Optional(Box::new(var_type)))
+        } else {
+            Ok(var_type)
+        }
+    }
 
-            // Ignore the current token if it is useless.
-            if self.token.token_type == TokenType::Comment {
-                self.advance_token();
-                continue;
-            }
+    fn get_current_identifier(&self) -> Result<String, ParseError> {
+        match self.token.token_type {
+            TokenType::Identifier(ref x) => Ok(x.clone()),
+            _ => Err(self.parse_error("Error while reading identifier.", &[])),
+        }
+    }
 
+    // Parse the whole program, a right brace that doesn't close a block is an error.
+    pub fn parse(&mut self) -> Result<Box<Expr>, ParseError> {
+        let block = self.parse_statements()?;
+
+        if self.token.token_type != TokenType::EOF {
+            let found = format!("{:?}", self.token.token_type);
+            return Err(self.parse_error("Unexpected token found. Expected: {}, Found: {} \
+                                         instead.",
+                                        &["\"EOF\"", &found]));
+        }
+
+        Ok(block)
+    }
+
+    // Parse statements until the end of the block or the code, the current token becomes the
+    // right brace or EOF.
+    fn parse_statements(&mut self) -> Result<Box<Expr>, ParseError> {
+        let mut block: Vec<Box<Expr>> = vec![];
+
+        // Read all tokens and create statements, then push it to the block.
+        loop {
             // Determine the parse type for current or (if not enough) next token.
             let stmt = match self.token.token_type.clone() {
-                TokenType::Keyword(ref x) if x == "number" => {
-                    Box::new(Expr {
-                        span: None,
-                        node: self.parse_integer(),
-                    })
-                }
-                TokenType::Keyword(ref x) if x == "string" => {
+                TokenType::Return => {
                     Box::new(Expr {
                         span: None,
-                        node: self.parse_string(),
+                        node: self.parse_return()?,
                     })
                 }
-                TokenType::Keyword(ref x) if x == "bool" => {
+                TokenType::Type(_) |
+                TokenType::Fn => {
+                    let doc = self.token.doc();
+                    let var_type = self.get_current_type()?;
                     Box::new(Expr {
                         span: None,
-                        node: self.parse_bool(),
+                        node: self.parse_declaration(var_type, doc)?,
                     })
                 }
-                TokenType::Identifier(ref x) if x == "if" => {
+                TokenType::If => {
                     Box::new(Expr {
                         span: None,
-                        node: self.parse_if(),
+                        node: self.parse_if()?,
                     })
                 }
                 TokenType::Identifier(ref x) => {
-                    // Eat LParen
-                    if self.eat_token("LParen") {
+                    // Eat LParen for function call or Equals for assignment
+                    if self.eat_token(TokenKind::LParen) {
+                        let call = self.parse_call(x.clone())?;
+                        self.expect_semicolon()?;
+                        Box::new(Expr {
+                            span: None,
+                            node: call,
+                        })
+                    } else if self.eat_token(TokenKind::Equals) {
+                        let value = self.parse_expression()?;
+                        self.expect_semicolon()?;
                         Box::new(Expr {
                             span: None,
-                            node: self.parse_call(x.clone()),
+                            node: Expr_::Assign(x.clone(), value),
                         })
                     } else {
-                        self.unexpected_token("LParen");
-                        unimplemented!();
+                        return Err(self.unexpected_token("LParen or Equals"));
                     }
                 }
                 TokenType::RBrace => break,
@@ -154,97 +253,138 @@ impl Parser {
                     }));
                     break;
                 }
+                ref x if keyword(x, self.language()).is_some() => {
+                    let keyword = keyword(x, self.language()).unwrap();
+                    return Err(self.parse_error("Keyword '{}' can't be used here.", &[keyword]));
+                }
                 _ => {
-                    self.unexpected_token(&self.token_to_string(&self.token.token_type));
-                    Box::new(Expr {
-                        span: None,
-                        node: Expr_::Nil,
-                    })
+                    let found = format!("{:?}", self.token.token_type);
+                    return Err(self.parse_error("Unexpected token found: {}.", &[&found]));
                 }
             };
 
             block.push(stmt);
         }
 
+        self.check_lex_error()?;
+
         // Return Boxed block statement.
-        Box::new(Expr {
+        Ok(Box::new(Expr {
             span: None,
             node: Expr_::Block(block),
-        })
+        }))
     }
 
-    fn parse_integer(&mut self) -> Expr_ {
-        let identifier: String;
-
+    fn parse_declaration(&mut self,
+                         var_type: Type,
+                         doc: Option<String>)
+                         -> Result<Expr_, ParseError> {
         // Eat identifier
-        if self.eat_token("Identifier") {
-            match self.token.token_type {
-                TokenType::Identifier(ref x) => identifier = x.clone(),
-                _ => unimplemented!(),
-            };
+        let identifier = self.expect_identifier()?;
 
-            // Eat equal symbol (=)
-            if self.eat_token("Equals") {
-                return self.calculate(identifier);
-            } else {
-                self.unexpected_token("Equals");
+        // Named function declaration 'fn name(params) { body }'
+        if var_type == Type::Function && self.eat_token(TokenKind::LParen) {
+            let function = self.parse_function()?;
+
+            // Skip the right brace of the function body
+            self.advance_token();
+            return Ok(Expr_::Declare(var_type,
+                                     identifier,
+                                     Some(Box::new(Expr {
+                                         span: None,
+                                         node: function,
+                                     })),
+                                     doc));
+        }
+
+        // Declaration without a value 'number? x;', only an optional variable can be nil.
+        if self.check_token(TokenKind::Semicolon) {
+            if let Type::Optional(_) = var_type {
+                self.expect_semicolon()?;
+                return Ok(Expr_::Declare(var_type, identifier, None, doc));
             }
-        } else {
-            self.unexpected_token("Identifier");
+
+            let name = format!("{:?}", identifier);
+            let type_name = self.language().type_name(&var_type);
+            return Err(self.parse_error("{} is declared without a value, it needs an optional \
+                                         type like '{}?'.",
+                                        &[&name, &type_name]));
         }
 
-        Expr_::Nil
+        // Eat equal symbol (=)
+        self.expect_token(TokenKind::Equals)?;
+
+        let value = self.parse_expression()?;
+        self.expect_semicolon()?;
+        Ok(Expr_::Declare(var_type, identifier, Some(value), doc))
+    }
+
+    fn parse_return(&mut self) -> Result<Expr_, ParseError> {
+        // 'return;' does not have a value
+        if self.check_token(TokenKind::Semicolon) {
+            self.expect_semicolon()?;
+            return Ok(Expr_::Return(None));
+        }
+
+        let value = self.parse_expression()?;
+        self.expect_semicolon()?;
+        Ok(Expr_::Return(Some(value)))
     }
 
     /**
-     * Calculate arithmetic expression with Shunting-Yard Algorithm
+     * Parse arithmetic expression with Shunting-Yard Algorithm
      */
-    fn calculate(&mut self, identifier: String) -> Expr_ {
+    fn parse_expression(&mut self) -> Result<Box<Expr>, ParseError> {
         let mut operator_stack: Vec<TokenType> = vec![];
         let mut rpn: Vec<RPNValue> = vec![];
-        let mut op_precedences: HashMap<TokenType, usize> = HashMap::new();
         let mut wait_exp = true;
+        let mut paren_depth = 0;
 
-        // Push operators to precendeces list
-        op_precedences.insert(TokenType::Plus, 2);
-        op_precedences.insert(TokenType::Minus, 2);
-        op_precedences.insert(TokenType::Multiple, 3);
-        op_precedences.insert(TokenType::Divide, 3);
-        op_precedences.insert(TokenType::Mod, 3);
-
-        // Loop for all numbers and operators
+        // Loop for all operands and operators
         loop {
-            if self.eat_token("Number") {
-                // Get first number
-                rpn.push(RPNValue::Number(self.get_current_number()));
-                wait_exp = false;
-            } else if wait_exp {
-                // If number is not set break the loop
-                break;
+            if wait_exp {
+                // Open a parenthesized sub expression or read an operand
+                if self.eat_token(TokenKind::LParen) {
+                    operator_stack.push(TokenType::LParen);
+                    paren_depth += 1;
+                } else {
+                    rpn.push(RPNValue::Operand(self.parse_operand()?));
+                    wait_exp = false;
+                }
             } else if self.eat_operator() {
-                // If eat an operator
-                let mut stack_len = operator_stack.len();
-
-                while stack_len > 0 &&
-                      op_precedences.get(&self.token.token_type) <
-                      op_precedences.get(&operator_stack[stack_len - 1]) {
-                    rpn.push(RPNValue::Operator(operator_stack[stack_len - 1].to_owned()));
-                    operator_stack.remove(stack_len - 1);
-                    stack_len -= 1;
+                // If eat an operator, pop operators with higher or equal precedence
+                let current = precedence(self.token.token_type.kind());
+
+                while let Some(top) = operator_stack.last().cloned() {
+                    if top == TokenType::LParen || precedence(top.kind()) < current {
+                        break;
+                    }
+
+                    rpn.push(RPNValue::Operator(top));
+                    operator_stack.pop();
                 }
 
                 operator_stack.push(self.token.token_type.clone());
                 wait_exp = true;
+            } else if paren_depth > 0 && self.eat_token(TokenKind::RParen) {
+                // Close the sub expression
+                while let Some(top) = operator_stack.pop() {
+                    if top == TokenType::LParen {
+                        break;
+                    }
+
+                    rpn.push(RPNValue::Operator(top));
+                }
+
+                paren_depth -= 1;
             } else {
-                // This means expression is ended and we need a semicolon check.
-                self.expect_semicolon();
+                // This means expression is ended, caller checks the next token.
                 break;
             }
         }
 
-        // wait_exp == true means line ended with an operator or line is empty.
-        if wait_exp {
-            self.unexpected_token("Number");
+        if paren_depth > 0 {
+            return Err(self.unexpected_token("RParen"));
         }
 
         // Popping stack and pushing to rpn queue.
@@ -252,247 +392,292 @@ impl Parser {
             rpn.push(RPNValue::Operator(op.to_owned()));
         }
 
-        // Calling soveRPN function and returning it as Expr_.
-        Expr_::Assign(identifier,
-                      Box::new(Expr {
-                          span: None,
-                          node: Expr_::Constant(Constant::Number(self.solve_rpn(rpn))),
-                      }))
+        self.build_rpn(rpn)
+    }
+
+    fn parse_operand(&mut self) -> Result<Box<Expr>, ParseError> {
+        // Eat the first token of the operand
+        self.advance_token();
+
+        let node = match self.token.token_type.clone() {
+            TokenType::Number(_) => Expr_::Constant(Constant::Number(self.get_current_number()?)),
+            TokenType::String(x) => Expr_::Constant(Constant::String(x)),
+            TokenType::Template(ref parts) => self.parse_template(parts)?,
+            TokenType::Char(ref x) => Expr_::Constant(Constant::Char(x.chars().next().unwrap())),
+            TokenType::True => Expr_::Constant(Constant::Bool(true)),
+            TokenType::False => Expr_::Constant(Constant::Bool(false)),
+            TokenType::Nil => Expr_::Constant(Constant::Nil),
+            TokenType::Identifier(identifier) => {
+                // Identifier followed by a left parenthesis is a function call
+                if self.eat_token(TokenKind::LParen) {
+                    self.parse_call(identifier)?
+                } else {
+                    Expr_::Variable(identifier)
+                }
+            }
+            TokenType::Fn => {
+                self.expect_token(TokenKind::LParen)?;
+                self.parse_function()?
+            }
+            TokenType::LBracket => self.parse_array()?,
+            TokenType::Minus => {
+                // Negation of an operand like '-x' or of a sub expression like '-(1 + x)'
+                if self.eat_token(TokenKind::LParen) {
+                    let value = self.parse_expression()?;
+                    self.expect_token(TokenKind::RParen)?;
+                    Expr_::Neg(value)
+                } else {
+                    Expr_::Neg(self.parse_operand()?)
+                }
+            }
+            ref x => return Err(self.unexpected("Expression", x)),
+        };
+
+        let mut operand = Box::new(Expr {
+            span: None,
+            node: node,
+        });
+
+        // Eat index expressions like 'name[0]'
+        while self.eat_token(TokenKind::LBracket) {
+            let index = self.parse_expression()?;
+
+            self.expect_token(TokenKind::RBracket)?;
+
+            operand = Box::new(Expr {
+                span: None,
+                node: Expr_::Index(operand, index),
+            });
+        }
+
+        Ok(operand)
+    }
+
+    fn parse_array(&mut self) -> Result<Expr_, ParseError> {
+        let mut elements: Vec<Box<Expr>> = vec![];
+
+        // Empty array
+        if self.eat_token(TokenKind::RBracket) {
+            return Ok(Expr_::Array(elements));
+        }
+
+        // Do While loop for elements
+        while {
+            elements.push(self.parse_expression()?);
+
+            self.eat_token(TokenKind::Comma) // Logical check for do while loop
+        } {}
+
+        // Eat RBracket
+        self.expect_token(TokenKind::RBracket)?;
+
+        Ok(Expr_::Array(elements))
+    }
+
+    fn parse_template(&self, parts: &[TemplatePart]) -> Result<Expr_, ParseError> {
+        let mut exprs: Vec<Box<Expr>> = vec![];
+
+        for part in parts {
+            match *part {
+                TemplatePart::Literal(ref x) => {
+                    exprs.push(Box::new(Expr {
+                        span: None,
+                        node: Expr_::Constant(Constant::String(x.clone())),
+                    }))
+                }
+                TemplatePart::Code(ref code) => {
+                    // Wrap the code in parentheses, so the expression starts after current token.
+                    // Position of an error is in the embedded code, so only the message is kept.
+                    // Embedded code is tokenized with the same options as the rest of the code.
+                    let code = io::Cursor::new(format!("({})", code).into_bytes());
+                    let lexer = Lexer::with_options(code, self.token_stream.options());
+                    let token_stream = TokenStream::from_lexer(lexer);
+                    let expr = Parser::new(token_stream, None)
+                        .parse_wrapped_expression()
+                        .map_err(|e| {
+                            self.parse_error(&e.cause.map_or(e.message, |x| x.message), &[])
+                        })?;
+                    exprs.push(expr);
+                }
+            }
+        }
+
+        Ok(Expr_::Interpolate(exprs))
+    }
+
+    /**
+     * Parse a stream that is a single expression wrapped in parentheses like '(1 + x)'.
+     * Expression parsing starts after the current token, so the left parenthesis is needed.
+     */
+    pub fn parse_wrapped_expression(&mut self) -> Result<Box<Expr>, ParseError> {
+        let expr = self.parse_expression()?;
+
+        if !self.eat_token(TokenKind::RParen) || !self.check_token(TokenKind::EOF) {
+            return Err(self.unexpected_token("End of expression"));
+        }
+
+        self.check_lex_error()?;
+        Ok(expr)
     }
 
-    fn solve_rpn(&mut self, rpn: Vec<RPNValue>) -> f64 {
-        let mut val_stack: Vec<f64> = vec![];
+    fn build_rpn(&mut self, rpn: Vec<RPNValue>) -> Result<Box<Expr>, ParseError> {
+        let mut val_stack: Vec<Box<Expr>> = vec![];
 
-        // Read rpn queue and calculate the value.
+        // Read rpn queue and build the expression tree.
         for value in rpn {
             match value {
-                RPNValue::Number(ref x) => val_stack.push(*x),
+                RPNValue::Operand(x) => val_stack.push(x),
                 RPNValue::Operator(ref x) => {
-                    let stack_length = val_stack.len();
-
-                    if stack_length >= 2 {
+                    if val_stack.len() >= 2 {
                         let first = val_stack.pop().unwrap();
                         let second = val_stack.pop().unwrap();
 
-                        match *x {
-                            TokenType::Plus => val_stack.push(second + first),
-                            TokenType::Minus => val_stack.push(second - first),
-                            TokenType::Multiple => val_stack.push(second * first),
-                            TokenType::Divide => val_stack.push(second / first),
-                            TokenType::Mod => val_stack.push(second % first),
-                            _ => self.unexpected_token(&self.token_to_string(x)),
-                        }
+                        let node = match *x {
+                            TokenType::Plus => Expr_::Add(second, first),
+                            TokenType::Minus => Expr_::Sub(second, first),
+                            TokenType::Multiple => Expr_::Mul(second, first),
+                            TokenType::Divide => Expr_::Div(second, first),
+                            TokenType::Mod => Expr_::Mod(second, first),
+                            TokenType::EqualEqual => Expr_::Eq(second, first),
+                            TokenType::NotEqual => Expr_::Ne(second, first),
+                            TokenType::Lesser => Expr_::Lt(second, first),
+                            TokenType::Greater => Expr_::Gt(second, first),
+                            TokenType::LesserEqual => Expr_::Le(second, first),
+                            TokenType::GreaterEqual => Expr_::Ge(second, first),
+                            TokenType::Coalesce => Expr_::Coalesce(second, first),
+                            _ => return Err(self.unexpected_token(&format!("{:?}", x))),
+                        };
+
+                        val_stack.push(Box::new(Expr {
+                            span: None,
+                            node: node,
+                        }));
                     } else {
-                        panic!("Parse error in arithmetic value. Check number assignment.");
+                        return Err(self.parse_error("Parse error in arithmetic expression. \
+                                                     Check the operands.",
+                                                    &[]));
                     }
                 }
             }
         }
 
-        val_stack[0]
+        val_stack.pop().ok_or_else(|| self.parse_error("Expression expected.", &[]))
     }
 
-    fn parse_string(&mut self) -> Expr_ {
-        let identifier: String;
-        let string: String;
-        let expr: Expr_;
+    /**
+     * Parse parameters and body of a function, current token is the left parenthesis.
+     */
+    fn parse_function(&mut self) -> Result<Expr_, ParseError> {
+        let mut params: Vec<Param> = vec![];
+
+        // Do While loop for parameters if parameter list is not empty
+        if !self.eat_token(TokenKind::RParen) {
+            while {
+                // Eat parameter type
+                let param_type = match self.peek_kind() {
+                    TokenKind::Type | TokenKind::Fn => {
+                        self.advance_token();
+                        self.get_current_type()?
+                    }
+                    _ => return Err(self.unexpected_token("Type")),
+                };
 
-        // Eat identifier
-        if self.eat_token("Identifier") {
-            match self.token.token_type {
-                TokenType::Identifier(ref x) => identifier = x.clone(),
-                _ => unimplemented!(),
-            };
+                // Eat parameter name
+                params.push(Param {
+                    param_type: param_type,
+                    name: self.expect_identifier()?,
+                });
 
-            // Eat equal symbol (=)
-            if self.eat_token("Equals") {
-                // Eat String
-                if self.eat_token("String") {
-                    // Create an expression and return it.
-                    match self.token.token_type.clone() {
-                        TokenType::String(ref y) => {
-                            string = y.clone();
-                            expr = Expr_::Assign(identifier,
-                                                 Box::new(Expr {
-                                                     span: None,
-                                                     node:
-                                                         Expr_::Constant(Constant::String(string)),
-                                                 }));
-                            self.expect_semicolon();
-                            expr
-                        }
-                        _ => unimplemented!(),
-                    };
-                }
-            } else {
-                self.unexpected_token("Equals");
-            }
-        } else {
-            self.unexpected_token("Identifier");
+                self.eat_token(TokenKind::Comma) // Logical check for do while loop
+            } {}
+
+            self.expect_token(TokenKind::RParen)?;
         }
 
-        Expr_::Nil
-    }
+        // Eat left brace for the start of the function body
+        self.expect_token(TokenKind::LBrace)?;
 
-    fn parse_bool(&mut self) -> Expr_ {
-        let identifier: String;
-        let bool_val: bool;
-        let expr: Expr_;
+        let body = self.parse_block()?;
+        Ok(Expr_::Function(params, body))
+    }
 
-        // Eat identifier
-        if self.eat_token("Identifier") {
-            match self.token.token_type {
-                TokenType::Identifier(ref x) => identifier = x.clone(),
-                _ => unimplemented!(),
-            };
+    // Parse statements of a block, current token is the left brace and becomes the right brace.
+    fn parse_block(&mut self) -> Result<Box<Expr>, ParseError> {
+        self.advance_token();
+        let block = self.parse_statements()?;
 
-            // Eat equal symbol (=)
-            if self.eat_token("Equals") {
-                // Eat True or False value
-                if self.eat_token("True") || self.eat_token("False") {
-                    // Create an expression and return it.
-                    match self.token.token_type.clone() {
-                        TokenType::True => bool_val = true,
-                        TokenType::False => bool_val = false,
-                        _ => unimplemented!(),
-                    };
-                    expr = Expr_::Assign(identifier,
-                                         Box::new(Expr {
-                                             span: None,
-                                             node: Expr_::Constant(Constant::Bool(bool_val)),
-                                         }));
-                    self.expect_semicolon();
-                    return expr;
-                }
-            } else {
-                self.unexpected_token("Equals");
-            }
-        } else {
-            self.unexpected_token("Identifier");
+        if self.token.token_type.kind() != TokenKind::RBrace {
+            return Err(self.parse_error("Unexpected end of file. Expected: \"RBrace\".", &[]));
         }
 
-        Expr_::Nil
+        Ok(block)
     }
 
-    fn parse_if(&mut self) -> Expr_ {
-        let mut condition_identifier: String = "".to_string();
-        let mut if_block: Box<Expr> = Box::new(Expr {
-            span: None,
-            node: Expr_::Nil,
-        });
-        let mut else_block: Option<Box<Expr>> = None;
-
-        // Eat condition identifier
-        if self.eat_token("LParen") {
-            if self.eat_token("Identifier") {
-                match self.token.token_type {
-                    TokenType::Identifier(ref x) => condition_identifier = x.clone(),
-                    _ => unimplemented!(),
-                };
-            } else {
-                self.unexpected_token("Identifier");
-            }
+    fn parse_if(&mut self) -> Result<Expr_, ParseError> {
+        // Eat condition expression
+        self.expect_token(TokenKind::LParen)?;
 
-            // Eat right parenthesis for end of the condition
-            if self.eat_token("RParen") {
-                // Eat left brace for the start of the if block
-                if self.eat_token("LBrace") {
-
-                    self.advance_token();
-                    if_block = self.parse();
-
-                    match self.token_stream.tokens[self.current_index + 1].token_type.clone() {
-                        TokenType::Identifier(ref x) if x == "else" => {
-                            self.advance_token();
-
-                            // Eat left brace for start of the else block
-                            if self.eat_token("LBrace") {
-                                self.advance_token();
-                                else_block = Some(self.parse());
-                            } else {
-                                self.unexpected_token("LBrace");
-                            }
-                        }
-                        _ => else_block = None,
-                    }
-                } else {
-                    self.unexpected_token("LBrace");
-                }
-            } else {
-                self.unexpected_token("RParen");
-            }
+        let condition = self.parse_expression()?;
+
+        // Eat right parenthesis for end of the condition
+        self.expect_token(TokenKind::RParen)?;
+
+        // Eat left brace for the start of the if block
+        self.expect_token(TokenKind::LBrace)?;
+
+        let if_block = self.parse_block()?;
+        let else_block = if self.eat_token(TokenKind::Else) {
+            // Eat left brace for start of the else block
+            self.expect_token(TokenKind::LBrace)?;
+
+            Some(self.parse_block()?)
         } else {
-            self.unexpected_token("LParen");
-        }
+            None
+        };
 
-        Expr_::If(Box::new(Expr {
-                      span: None,
-                      node: Expr_::Variable(condition_identifier),
-                  }),
-                  if_block,
-                  else_block)
+        // Skip the right brace of the last block
+        self.advance_token();
+        Ok(Expr_::If(condition, if_block, else_block))
     }
 
-    fn parse_call(&mut self, identifier: String) -> Expr_ {
-        let mut string: String;
-        let expr: Expr_;
+    fn parse_call(&mut self, identifier: String) -> Result<Expr_, ParseError> {
         let mut params: Vec<Box<Expr>> = vec![];
 
+        // Function call without parameters
+        if self.eat_token(TokenKind::RParen) {
+            return Ok(Expr_::Call(identifier, params));
+        }
+
         // Do While loop for parameters
         while {
-            // Eat String
-            if self.eat_token("String") {
-                // Create an expression and return it.
-                match self.token.token_type {
-                    TokenType::String(ref x) => string = x.clone(),
-                    _ => unimplemented!(),
-                };
-
-                let boxed_expr = Box::new(Expr {
-                    span: None,
-                    node: Expr_::Constant(Constant::String(string)),
-                });
-                params.push(boxed_expr);
+            params.push(self.parse_expression()?);
 
-            }
-            // Eat identifier
-            else if self.eat_token("Identifier") {
-                // Create an expression and return it.
-                match self.token.token_type {
-                    TokenType::Identifier(ref x) => string = x.clone(),
-                    _ => unimplemented!(),
-                };
-
-                let boxed_expr = Box::new(Expr {
-                    span: None,
-                    node: Expr_::Variable(string),
-                });
-                params.push(boxed_expr);
+            self.eat_token(TokenKind::Comma) // Logical check for do while loop
+        } {}
 
-            } else {
-                self.unexpected_token("Identifier or String");
-            }
+        // Eat RParen
+        self.expect_token(TokenKind::RParen)?;
 
-            self.eat_token("Comma") // Logical check for do while loop
-        } {}
+        Ok(Expr_::Call(identifier, params))
+    }
 
-        expr = Expr_::Call(identifier, params);
+    fn expect_semicolon(&mut self) -> Result<(), ParseError> {
+        self.expect_token(TokenKind::Semicolon)?;
 
-        // Eat RParen
-        if self.eat_token("RParen") {
-            self.expect_semicolon();
-            expr
-        } else {
-            self.unexpected_token("RParen");
-            Expr_::Nil
-        }
+        self.advance_token();
+        Ok(())
     }
+}
 
-    fn expect_semicolon(&mut self) {
-        if self.eat_token("Semicolon") {
-            self.advance_token();
-        } else {
-            self.unexpected_token("Semicolon");
-        }
+// Precedence of a binary operator, operators with a higher one are applied first.
+fn precedence(kind: TokenKind) -> Option<usize> {
+    match kind {
+        TokenKind::EqualEqual | TokenKind::NotEqual => Some(1),
+        TokenKind::Greater | TokenKind::Lesser | TokenKind::GreaterEqual |
+        TokenKind::LesserEqual => Some(2),
+        TokenKind::Coalesce => Some(3),
+        TokenKind::Plus | TokenKind::Minus => Some(4),
+        TokenKind::Multiple | TokenKind::Divide | TokenKind::Mod => Some(5),
+        _ => None,
     }
 }
@@ -0,0 +1,250 @@
+// Interpreter for Basic C like language
+// REPL Module
+//
+
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use interpreter::{Engine, Error, Value};
+use interpreter::lexer::{LexerOptions, TokenType};
+
+use {describe_token, MAX_CALL_DEPTH};
+
+// Number of the previous inputs that are kept, older ones are forgotten.
+const MAX_HISTORY: usize = 1000;
+
+const HELP: &'static str = "\
+Statements are run and the values of expressions are printed. Input continues on the next
+line while braces, parentheses or brackets are open, an empty line ends it.
+
+    :vars           Print the global variables
+    :ast <code>     Print the syntax tree of the code
+    :tokens <code>  Print the tokens of the code
+    :history        Print the previous inputs
+    :reset          Forget all variables and functions
+    :help           Print this message
+    :quit           Exit, end of input (Ctrl-D) and 'exit(code)' also exit";
+
+pub struct Repl {
+    engine: Engine,
+    strict: bool,
+    lexer_options: LexerOptions,
+    history: VecDeque<String>,
+    history_file: Option<PathBuf>, // Inputs are saved here to be read in the next session
+}
+
+impl Repl {
+    pub fn new(strict: bool, lexer_options: LexerOptions) -> Repl {
+        let history_file = env::var_os("HOME").map(|home| {
+            PathBuf::from(home).join(".interpreter_history")
+        });
+
+        let history = match history_file {
+            Some(ref path) => read_history(path),
+            None => VecDeque::new(),
+        };
+
+        Repl {
+            engine: new_engine(strict, lexer_options),
+            strict: strict,
+            lexer_options: lexer_options,
+            history: history,
+            history_file: history_file,
+        }
+    }
+
+    // Read and evaluate the inputs until the end, returns the exit status.
+    pub fn run(&mut self) -> i32 {
+        println!("Type :help for help, :quit to exit.");
+
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        let mut input = String::new();
+
+        loop {
+            print!("{}", if input.is_empty() { ">> " } else { ".. " });
+            let _ = io::stdout().flush();
+
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break,
+            };
+
+            // Empty line ends the unfinished input, so its errors can be seen.
+            if line.trim().is_empty() {
+                if !input.is_empty() {
+                    let source = input.clone();
+                    input.clear();
+                    self.eval(&source);
+                }
+
+                if let Some(code) = self.engine.exit_code() {
+                    return code;
+                }
+                continue;
+            }
+
+            if input.is_empty() && line.trim().starts_with(':') {
+                self.add_history(line.trim());
+                if !self.command(line.trim()) {
+                    break;
+                }
+                continue;
+            }
+
+            input.push_str(&line);
+            input.push('\n');
+
+            if !self.is_complete(&input) {
+                continue;
+            }
+
+            let source = input.clone();
+            input.clear();
+            self.eval(&source);
+
+            if let Some(code) = self.engine.exit_code() {
+                return code;
+            }
+        }
+
+        println!();
+        0
+    }
+
+    // Run a meta command, returns false if the REPL should exit.
+    fn command(&mut self, line: &str) -> bool {
+        let (name, rest) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+
+        match name {
+            ":quit" | ":q" => return false,
+            ":help" => println!("{}", HELP),
+            ":vars" => {
+                for (name, declared_type, value) in self.engine.interpreter().globals() {
+                    println!("{}: {} = {}", name, declared_type, value);
+                }
+            }
+            ":ast" => {
+                // Show the expression itself if the code is a single expression.
+                let ast = self.engine
+                    .parse_expression(rest)
+                    .or_else(|_| self.engine.parse(rest));
+
+                match ast {
+                    Ok(ast) => println!("{:#?}", ast),
+                    Err(why) => println!("{}", why),
+                }
+            }
+            ":tokens" => {
+                match self.engine.tokenize(rest) {
+                    Ok(tokens) => {
+                        for token in tokens {
+                            println!("{}", describe_token(&token.token_type));
+                        }
+                    }
+                    Err(why) => println!("{}", why),
+                }
+            }
+            ":history" => {
+                for (i, line) in self.history.iter().enumerate() {
+                    println!("{:5}  {}", i + 1, line);
+                }
+            }
+            ":reset" => {
+                self.engine = new_engine(self.strict, self.lexer_options);
+                println!("State is reset.");
+            }
+            _ => println!("Unknown command {:?}, type :help for help.", name),
+        }
+
+        true
+    }
+
+    fn eval(&mut self, source: &str) {
+        self.add_history(source.trim_end());
+
+        // Expressions are evaluated for their value, other inputs are run as statements.
+        let result = match self.engine.eval_expression(source) {
+            Err(Error::Lex(_)) |
+            Err(Error::Parse(_)) => self.engine.eval(source),
+            result => result,
+        };
+
+        match result {
+            Ok(Value::Nil) => {}
+            Ok(Value::String(x)) => println!("{:?}", x),
+            Ok(Value::Char(x)) => println!("{:?}", x),
+            Ok(value) => println!("{}", value),
+            Err(why) => println!("{}", why),
+        }
+    }
+
+    // Input is complete if all braces, parentheses and brackets are closed. Input that can't
+    // be tokenized is complete too, so the error is shown.
+    fn is_complete(&self, input: &str) -> bool {
+        let tokens = match self.engine.tokenize(input) {
+            Ok(tokens) => tokens,
+            Err(_) => return true,
+        };
+
+        let mut depth = 0;
+        for token in tokens {
+            match token.token_type {
+                TokenType::LBrace | TokenType::LParen | TokenType::LBracket => depth += 1,
+                TokenType::RBrace | TokenType::RParen | TokenType::RBracket => depth -= 1,
+                _ => {}
+            }
+        }
+
+        depth <= 0
+    }
+
+    fn add_history(&mut self, input: &str) {
+        // Inputs of many lines are saved as one line.
+        let entry = input.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+        self.history.push_back(entry.clone());
+        if self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+        }
+
+        if let Some(ref path) = self.history_file {
+            let file = OpenOptions::new().create(true).append(true).open(path);
+            if let Ok(mut file) = file {
+                let _ = writeln!(file, "{}", entry);
+            }
+        }
+    }
+}
+
+// Read the last inputs of the previous sessions, the file may not exist yet. Reading stops at
+// the first error, like the one of a path that is a directory.
+fn read_history(path: &Path) -> VecDeque<String> {
+    let mut history = VecDeque::new();
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return history,
+    };
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        history.push_back(line);
+        if history.len() > MAX_HISTORY {
+            history.pop_front();
+        }
+    }
+
+    history
+}
+
+fn new_engine(strict: bool, lexer_options: LexerOptions) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_lexer_options(lexer_options);
+    engine.interpreter().set_strict(strict);
+    engine.interpreter().set_max_call_depth(MAX_CALL_DEPTH);
+    engine
+}
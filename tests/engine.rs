@@ -0,0 +1,374 @@
+// Interpreter for Basic C like language
+// Engine Tests
+//
+
+extern crate interpreter;
+
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+use interpreter::{Engine, Type, Value};
+use interpreter::lexer::LexerOptions;
+
+// Program output shared with the interpreter, it is read after the run.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn engine(input: &str, output: &Output) -> Engine {
+    let mut engine = Engine::new();
+    engine.interpreter().set_output(output.clone());
+    engine.interpreter().set_input(Cursor::new(input.as_bytes().to_vec()));
+    engine
+}
+
+// Output of the code run with the given input, or the error message.
+fn run_with_input(code: &str, input: &str) -> Result<String, String> {
+    let output = Output::default();
+    let mut engine = engine(input, &output);
+
+    match engine.eval(code) {
+        Ok(_) => Ok(String::from_utf8(output.0.borrow().clone()).unwrap()),
+        Err(why) => Err(why.to_string()),
+    }
+}
+
+// Value of the code run by the engine, or the error message.
+fn eval(engine: &mut Engine, code: &str) -> Result<String, String> {
+    match engine.eval(code) {
+        Ok(value) => Ok(value.to_string()),
+        Err(why) => Err(why.to_string()),
+    }
+}
+
+fn run(code: &str) -> Result<String, String> {
+    run_with_input(code, "")
+}
+
+fn assert_error(result: Result<String, String>, message: &str) {
+    match result {
+        Err(ref why) if why.contains(message) => {}
+        x => panic!("expected an error with {:?}, found {:?}", message, x),
+    }
+}
+
+#[test]
+fn closures() {
+    let code = "
+        fn counter() {
+            number count = 0;
+            fn next = fn() {
+                count = count + 1;
+                return count;
+            };
+            return next;
+        }
+
+        fn apply(fn f, number x) {
+            return f(x);
+        }
+
+        fn next = counter();
+        next();
+        print(next(), \" \", apply(fn(number x) { return x * 2; }, 21));
+    ";
+
+    assert_eq!(run(code), Ok("2 42\n".to_string()));
+}
+
+#[test]
+fn call_depth_limit() {
+    let code = "
+        fn down(number n) {
+            if (n == 0) {
+                return 0;
+            }
+            return down(n - 1);
+        }
+    ";
+
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.eval(code).unwrap();
+    assert_eq!(engine.eval("return down(50);").unwrap(), Value::Number(0.0));
+    assert_error(eval(&mut engine, "return down(1000);"),
+                 "Maximum call depth of 64 is exceeded while calling \"down\"!");
+
+    engine.interpreter().set_max_call_depth(10);
+    assert_error(eval(&mut engine, "return down(50);"),
+                 "Maximum call depth of 10 is exceeded");
+}
+
+#[test]
+fn operator_precedence() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval("return 1 + 2 * 3 - -(4 - 1) % 2;").unwrap(), Value::Number(8.0));
+    assert_eq!(engine.eval("return nil ?? 1 + 2 == 3;").unwrap(), Value::Bool(true));
+    assert_error(eval(&mut engine, "return (1 + 2;"), "Expected: \"RParen\"");
+}
+
+#[test]
+fn nil_and_coalesce() {
+    let code = "
+        number? x;
+        number y = x ?? 5;
+        x = 7;
+        print(y, \" \", x ?? 0, \" \", nil ?? \"d\");
+    ";
+
+    assert_eq!(run(code), Ok("5 7 d\n".to_string()));
+    assert_error(run("number? x; number y = x * 2;"),
+                 "Nil value found where a number is needed!");
+    assert_error(run("number x;"), "needs an optional type like 'number?'");
+}
+
+#[test]
+fn chars() {
+    let code = "
+        string s = \"héllo\";
+        char c = s[1];
+        print(c, \" \", c == 'é', \" \", ord('a'), \" \", chr(98), \" \", 'a' < 'b');
+    ";
+
+    assert_eq!(run(code), Ok("é true 97 b true\n".to_string()));
+    assert_error(run("char c = \"abc\"[3];"), "Index 3 is out of range for length 3!");
+}
+
+#[test]
+fn escapes_and_interpolation() {
+    let code = r#"
+        string name = "Ada";
+        number age = 36;
+        print("tab\there \"quoted\" \\ \u{1F600}");
+        print("Hello ${name}, next year ${age + 1}");
+        print("""two
+lines""");
+    "#;
+
+    assert_eq!(run(code),
+               Ok("tab\there \"quoted\" \\ \u{1F600}\nHello Ada, next year 37\ntwo\nlines\n"
+                   .to_string()));
+    assert_error(run("string s = \"open;"), "Unterminated string literal");
+}
+
+#[test]
+fn string_builtins() {
+    let code = r#"
+        string s = "  Hello, World  ";
+        string t = trim(s);
+        print(length(t), " ", substring(t, 0, 5), " ", find(t, "World"), " ", find(t, "x") ?? -1);
+        print(replace(t, "l", "L"), " ", upper("a"), lower("B"), " ", repeat("ab", 2));
+        print(join(split("a,b,c", ","), "-"), " ", startsWith(t, "He"), " ", endsWith(t, "x"));
+    "#;
+
+    assert_eq!(run(code),
+               Ok("12 Hello 7 -1\nHeLLo, WorLd Ab abab\na-b-c true false\n".to_string()));
+    assert_error(run("print(substring(\"abc\", 2, 5));"),
+                 "Index 5 is out of range for length 3!");
+    assert_error(run("print(length(1));"),
+                 "\"length\" expects a string parameter but number given.");
+}
+
+#[test]
+fn math_builtins() {
+    let code = "
+        print(sqrt(16), \" \", pow(2, 10), \" \", abs(-3), \" \", min(3, 1, 2), \" \",
+              max(3, 1, 2));
+        number half = 2.5;
+        print(floor(half), \" \", ceil(half), \" \", round(half), \" \", log(8, 2), \" \", PI > 3);
+        print(sqrt(-1), \" \", 0xFF + 0b1010 + 0o17, \" \", 1_000 * 1e-3);
+    ";
+
+    assert_eq!(run(code), Ok("4 1024 3 1 3\n2 3 3 3 true\nNaN 280 1\n".to_string()));
+
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.interpreter().set_strict(true);
+    assert_error(eval(&mut engine, "print(sqrt(-1));"), "\"sqrt\" is not defined for -1!");
+}
+
+#[test]
+fn conversions() {
+    let code = r#"
+        number? n = toNumber("42");
+        print(n ?? 0, " ", toNumber("x") ?? -1, " ", toString(7 / 2), " ", toBool("true"));
+        print(typeof(1), " ", typeof("a"), " ", typeof(nil), " ", typeof(fn() { return; }));
+    "#;
+
+    assert_eq!(run(code), Ok("42 -1 3.5 true\nnumber string nil fn\n".to_string()));
+}
+
+#[test]
+fn typed_input() {
+    let code = "
+        number x = 0;
+        bool b = false;
+        string s = \"\";
+        read(x, b);
+        read(s);
+        print(x + 1, \" \", b, \" \", s);
+    ";
+
+    assert_eq!(run_with_input(code, "41 true\nrest of line\n"),
+               Ok("42 true rest of line\n".to_string()));
+    assert_error(run_with_input(code, "abc\n"),
+                 "Expected a number value for \"x\" but \"abc\" found in input!");
+    assert_error(run_with_input(code, "1"), "End of input reached while reading \"b\"!");
+}
+
+#[test]
+fn native_functions() {
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.interpreter().register_function("twice", vec![Type::Number], |args| {
+        match args[0] {
+            Value::Number(x) => Ok(Value::Number(x * 2.0)),
+            _ => Err("unreachable".to_string()),
+        }
+    });
+    engine.interpreter().register_function("fail", vec![], |_| Err("failed".to_string()));
+
+    assert_eq!(engine.eval("return twice(21);").unwrap(), Value::Number(42.0));
+    assert_error(eval(&mut engine, "twice(\"a\");"),
+                 "Parameter 1 of \"twice\" must be number but string given.");
+    assert_eq!(eval(&mut engine, "fail();"), Err("Runtime error: failed".to_string()));
+}
+
+#[test]
+fn script_arguments_and_environment() {
+    env::set_var("INTERPRETER_TEST_VALUE", "set");
+    let code = r#"
+        print(join(args(), ","), " ", env("INTERPRETER_TEST_VALUE") ?? "-");
+        print(env("INTERPRETER_TEST_MISSING") ?? "-", " ", env("A=B") ?? "-");
+    "#;
+
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.interpreter().set_args(vec!["first".to_string(), "second".to_string()]);
+    engine.eval(code).unwrap();
+    assert_eq!(String::from_utf8(output.0.borrow().clone()).unwrap(),
+               "first,second set\n- -\n");
+    assert_error(eval(&mut engine, "args(1);"), "\"args\" expects 0 parameters but 1 given.");
+}
+
+#[test]
+fn exit_codes() {
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    assert_eq!(engine.eval("print(\"before\"); exit(3); print(\"after\");").unwrap(), Value::Nil);
+    assert_eq!(engine.exit_code(), Some(3));
+    assert_eq!(String::from_utf8(output.0.borrow().clone()).unwrap(), "before\n");
+
+    engine.eval("fn stop() { exit(); } stop();").unwrap();
+    assert_eq!(engine.exit_code(), Some(0));
+    engine.eval("number x = 1;").unwrap();
+    assert_eq!(engine.exit_code(), None);
+
+    assert_error(eval(&mut engine, "exit(256);"),
+                 "Exit code must be an integer from 0 to 255, found 256.");
+    assert_error(eval(&mut engine, "exit(\"a\");"),
+                 "Exit code must be a number but string given.");
+}
+
+#[test]
+fn globals() {
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.set_global("limit", Type::Number, Value::Number(10.0)).unwrap();
+    assert!(engine.set_global("name", Type::Number, Value::Nil).is_err());
+    assert!(engine.set_global("name", Type::Nil, Value::Nil).is_err());
+
+    engine.eval("number twice = limit * 2;").unwrap();
+    assert_eq!(engine.get_global("twice"), Some(Value::Number(20.0)));
+}
+
+#[test]
+fn case_sensitive_names() {
+    assert_eq!(run("number Count = 1; number count = 2; print(Count, count, toString(PI > 3));"),
+               Ok("12true\n".to_string()));
+    assert_error(run("print(tostring(1));"), "\"tostring\" function not found!");
+
+    // Legacy programs lowercase every word, built-ins are known by their lowercase names.
+    let output = Output::default();
+    let mut engine = engine("", &output);
+    engine.set_lexer_options(LexerOptions { case_insensitive: true, ..LexerOptions::default() });
+    engine.eval("Number Count = 1; count = COUNT + 1; YAZ(toString(count), tostring(pi > 3));")
+        .unwrap();
+    assert_eq!(String::from_utf8(output.0.borrow().clone()).unwrap(), "2true\n");
+}
+
+#[test]
+fn type_check() {
+    let check = |code: &str| Engine::new().check(code).map_err(|why| why.to_string());
+
+    assert_eq!(check("number x = 1; string s = toString(x) + \"!\";"), Ok(()));
+    assert_eq!(check("number x = 1; string s = x * 2;"),
+               Err("Type error: \"s\" is declared as string but assigned a number value."
+                   .to_string()));
+    assert_eq!(check("number if = 1;"),
+               Err("Parse error: Unexpected token found. Expected: \"Identifier\", Found: If \
+                    instead."
+                   .to_string()));
+    assert_eq!(check("while (true) {}"),
+               Err("Parse error: Keyword 'while' can't be used here.".to_string()));
+    assert_eq!(check("number x = 1; } x = 2;"),
+               Err("Parse error: Unexpected token found. Expected: \"EOF\", Found: RBrace \
+                    instead."
+                   .to_string()));
+}
+
+#[test]
+fn languages() {
+    // Built-ins have the names of the language of the code, names of the other one are free.
+    let code = "// dil: türkçe
+        sayı print = 3;
+        metin s = \"merhaba\";
+        eğer (uzunluk(s) > print) {
+            yaz(büyükHarf(s), \" \", karekök(16), \" \", PI > print);
+        }
+    ";
+    assert_eq!(run(code), Ok("MERHABA 4 true\n".to_string()));
+    assert_error(run("number yaz = 1; print(uzunluk(\"a\"));"),
+                 "Runtime error: \"uzunluk\" function not found!");
+
+    // Errors of each stage are in the language of the code.
+    assert_error(run("// dil: türkçe\nyaz(print(1));"),
+                 "Çalışma hatası: \"print\" fonksiyonu bulunamadı!");
+    assert_error(run("// dil: türkçe\nyaz(uzunluk(1));"),
+                 "Çalışma hatası: \"uzunluk\" bir metin parametre bekliyor ama sayı verildi.");
+    assert_error(run("// dil: türkçe\nsayı x = 1 + \"a\";"),
+                 "Çalışma hatası: sayı ve metin değerleri toplanamaz.");
+    assert_error(run("// dil: türkçe\nsayı x = 1 @"),
+                 "Sözdizimi hatası: Beklenmeyen karakter: '@', satır 2, sütun 12!");
+    assert_eq!(Engine::new()
+                   .check("// dil: türkçe\nsayı x = 1; metin s = x * 2;")
+                   .map_err(|why| why.to_string()),
+               Err("Tür hatası: \"s\" metin olarak tanımlandı ama sayı değeri atandı."
+                   .to_string()));
+}
+
+#[test]
+fn builtins_can_be_redeclared() {
+    // Functions of the program are called instead of the built-ins with the same name.
+    let code = "
+        fn print(string s) {
+            return s + \"!\";
+        }
+        return print(\"a\");
+    ";
+
+    assert_eq!(Engine::new().eval(code).unwrap(), Value::String("a!".to_string()));
+}